@@ -1,6 +1,9 @@
 pub mod constraints;
 pub mod digit;
 mod parser;
+pub mod rating;
 pub mod solution;
+pub mod solve_log;
 mod solver;
+pub mod strategy;
 pub mod sudoku;
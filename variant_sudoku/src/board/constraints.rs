@@ -1,14 +1,25 @@
+pub(crate) mod arrow;
+pub(crate) mod diagonal;
+pub(crate) mod jigsaw;
 pub(crate) mod killer;
+pub(crate) mod kropki;
 pub(crate) mod standard;
+pub(crate) mod thermo;
 
 use std::{any::Any, rc::Rc};
 
 use crate::{
-    board::sudoku::{Cell, DidUpdateGrid},
+    board::{
+        digit::Symbol,
+        sudoku::{Cell, DidUpdateGrid},
+    },
     errors::SudokuError,
     Sudoku,
 };
 
+/// Two candidates (a symbol in a cell) which can not both be true.
+pub type WeakLink = ((Cell, Symbol), (Cell, Symbol));
+
 pub trait Constraint: Any {
     /// For each constraint, this notify update should be called to indicate it should check for any propogations.
     ///
@@ -22,7 +33,29 @@ pub trait Constraint: Any {
 
     fn as_any(&self) -> &dyn Any;
 
-    fn use_strategies(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError>;
+    /// Returns true if the cells placed so far already break this constraint.
+    ///
+    /// Most contradictions show up as a cell with no candidates left, but some (like a
+    /// fully placed killer cage with the wrong sum) can only be caught by checking the
+    /// constraint itself. This is what lets the brute force solver discard a bad guess.
+    fn is_violated(&self, sudoku: &Sudoku) -> bool;
+
+    /// Pairs of candidates this constraint stops from both being true.
+    ///
+    /// Chaining strategies already link candidates sharing a cell or a house, so only constraints
+    /// relating the symbols of different cells (like Kropki dots or thermometers) need to add any.
+    fn get_weak_links(&self, _sudoku: &Sudoku) -> Vec<WeakLink> {
+        vec![]
+    }
+
+    /// Whether this constraint stays satisfied when two symbols swap places around `rectangle`,
+    /// with corners ordered top left, top right, bottom left, bottom right.
+    ///
+    /// Uniqueness strategies rely on such a swap giving a second solution, so they leave alone
+    /// any rectangle a constraint can not vouch for.
+    fn allows_swap(&self, _sudoku: &Sudoku, _rectangle: &[Cell; 4]) -> bool {
+        false
+    }
 }
 
 pub type RcConstraint = Rc<dyn Constraint>;
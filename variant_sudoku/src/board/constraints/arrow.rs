@@ -0,0 +1,193 @@
+use std::{any::Any, collections::HashSet};
+
+use log::{debug, trace};
+
+use crate::{
+    board::{
+        constraints::{
+            killer::{Cage, KillerMarking},
+            standard::{share_house, House, HouseSet},
+        },
+        digit::Symbol,
+        strategy::Strategy,
+        sudoku::{Cell, DidUpdateGrid},
+    },
+    errors::SudokuError,
+    Constraint, Sudoku,
+};
+
+/// The weight logged for narrowing an arrow down to the sums its circle can make.
+const ARROW_WEIGHT: f32 = 2.0;
+
+/// The digits along each arrow add up to the number in its circle.
+///
+/// Digits on an arrow may repeat, unless the cells share a house.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Arrow {
+    arrows: Vec<ArrowLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ArrowLine {
+    /// One or more cells, read as a decimal number from the first cell to the last (a "pill").
+    circle: House,
+    line: House,
+}
+
+impl Arrow {
+    pub(crate) fn new(arrows: Vec<ArrowLine>) -> Self {
+        debug!("Arrow Created");
+        Self { arrows }
+    }
+}
+
+impl ArrowLine {
+    pub(crate) fn new(circle: House, line: House) -> Self {
+        Self { circle, line }
+    }
+
+    fn contains(&self, cell: &Cell) -> bool {
+        self.circle.contains(cell) || self.line.contains(cell)
+    }
+
+    /// Every number the circle can show, with the symbols making it up.
+    fn get_circle_options(
+        &self,
+        sudoku: &Sudoku,
+        houses: &HouseSet,
+    ) -> Result<Vec<(u32, Vec<Symbol>)>, SudokuError> {
+        let mut options: Vec<(u32, Vec<Symbol>)> = vec![(0, vec![])];
+        for cell in &self.circle {
+            let candidates = sudoku.get_cell(cell)?.0.clone();
+            let mut next = vec![];
+            for (number, symbols) in &options {
+                for symbol in &candidates {
+                    let repeats = symbols.iter().zip(&self.circle).any(|(other, other_cell)| {
+                        other == symbol && share_house(houses, other_cell, cell)
+                    });
+                    let value = match sudoku.get_value(symbol) {
+                        Some(v) if !repeats => v,
+                        _ => continue,
+                    };
+                    let mut symbols = symbols.clone();
+                    symbols.push(*symbol);
+                    next.push((number * 10 + value, symbols));
+                }
+            }
+            options = next;
+        }
+        Ok(options)
+    }
+
+    /// Keeps only the candidates that let the line add up to a number the circle can show.
+    fn prune(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        let houses = sudoku.get_houses();
+        let circle_options = self.get_circle_options(sudoku, &houses)?;
+        let numbers: HashSet<u32> = circle_options.iter().map(|(n, _)| *n).collect();
+        // Digits on the line only have to differ where they share a house, like a cage
+        // which is not unique.
+        let line_options = Cage::new(self.line.clone(), KillerMarking::None, false)
+            .get_sum_options(sudoku, &numbers)?;
+        let mut keep_circle: Vec<HashSet<Symbol>> = vec![HashSet::new(); self.circle.len()];
+        for (_, symbols) in circle_options
+            .iter()
+            .filter(|(n, _)| line_options.totals.contains(n))
+        {
+            for (i, symbol) in symbols.iter().enumerate() {
+                keep_circle[i].insert(*symbol);
+            }
+        }
+        let cells = self.circle.iter().zip(keep_circle.iter());
+        let cells = cells.chain(self.line.iter().zip(line_options.per_cell.iter()));
+        let mut did_update = false;
+        for (cell, keep) in cells {
+            let candidates = sudoku.get_cell(cell)?.0.clone();
+            for symbol in candidates.iter().filter(|s| !keep.contains(s)) {
+                trace!("Arrow removing {symbol:?} from {cell:?}, it cannot make the sum");
+                did_update |= sudoku.remove_candidate(cell, symbol)?;
+            }
+        }
+        Ok(did_update)
+    }
+
+    fn is_violated(&self, sudoku: &Sudoku) -> bool {
+        let value = |c: &Cell| {
+            sudoku
+                .get_cell(c)
+                .ok()
+                .and_then(|d| d.try_get_solved())
+                .and_then(|s| sudoku.get_value(s))
+        };
+        let circle: Option<Vec<u32>> = self.circle.iter().map(value).collect();
+        let line: Option<Vec<u32>> = self.line.iter().map(value).collect();
+        match (circle, line) {
+            (Some(circle), Some(line)) => {
+                circle.iter().fold(0, |n, d| n * 10 + d) != line.iter().sum::<u32>()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Constraint for Arrow {
+    fn notify_update(
+        &self,
+        sudoku: &mut Sudoku,
+        cell: &Cell,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let mut did_update = false;
+        for arrow in self.arrows.iter().filter(|a| a.contains(cell)) {
+            did_update |= arrow.prune(sudoku)?;
+        }
+        Ok(did_update)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn allows_swap(&self, _sudoku: &Sudoku, rectangle: &[Cell; 4]) -> bool {
+        !self
+            .arrows
+            .iter()
+            .any(|a| rectangle.iter().any(|c| a.contains(c)))
+    }
+
+    fn is_violated(&self, sudoku: &Sudoku) -> bool {
+        self.arrows.iter().any(|a| a.is_violated(sudoku))
+    }
+}
+
+/// Narrows each arrow down to the sums its circle can make.
+pub(crate) struct ArrowSum;
+
+impl Strategy for ArrowSum {
+    fn name(&self) -> &str {
+        "Arrow Sum"
+    }
+
+    fn weight(&self) -> f32 {
+        ARROW_WEIGHT
+    }
+
+    fn is_applicable(&self, sudoku: &Sudoku) -> bool {
+        sudoku.constraints.iter().any(|c| c.as_any().is::<Arrow>())
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        debug!("Running Arrow Sum");
+        let arrows: Vec<ArrowLine> = sudoku
+            .constraints
+            .iter()
+            .filter_map(|c| c.as_any().downcast_ref::<Arrow>())
+            .flat_map(|a| a.arrows.iter().cloned())
+            .collect();
+        for arrow in &arrows {
+            if arrow.prune(sudoku)? {
+                sudoku.note_regions(&[&arrow.circle, &arrow.line]);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
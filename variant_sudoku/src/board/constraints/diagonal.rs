@@ -0,0 +1,50 @@
+use crate::{
+    board::{constraints::standard::House, sudoku::Cell},
+    errors::SudokuError,
+};
+
+/// A diagonal of the grid on which each symbol may appear at most once.
+///
+/// The diagonal itself is not a constraint, it is turned into a `HouseUnique::Custom` house
+/// so that every house strategy picks it up for free.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Diagonal {
+    /// Runs from the bottom-left corner up to the top-right corner.
+    Positive,
+    /// Runs from the top-left corner down to the bottom-right corner.
+    Negative,
+}
+
+impl Diagonal {
+    pub(crate) fn from_variant(variant: &str) -> Result<Self, SudokuError> {
+        let ok = match variant {
+            "positive" => Diagonal::Positive,
+            "negative" => Diagonal::Negative,
+            v => {
+                return Err(SudokuError::ConstraintPredicateInvalid(format!(
+                    "Unknown diagonal variant {v:?}, expected positive or negative"
+                )))
+            }
+        };
+        Ok(ok)
+    }
+
+    pub(crate) fn get_house(&self, size: (usize, usize)) -> Result<House, SudokuError> {
+        let (rows, cols) = size;
+        if rows != cols {
+            return Err(SudokuError::UnsupportedConstraint(format!(
+                "Diagonal on a non-square grid of size {size:?}"
+            )));
+        }
+        let house = (0..rows)
+            .map(|i| match self {
+                Diagonal::Positive => Cell {
+                    row: rows - 1 - i,
+                    col: i,
+                },
+                Diagonal::Negative => Cell { row: i, col: i },
+            })
+            .collect();
+        Ok(house)
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{
+    board::{constraints::standard::House, sudoku::Cell},
+    errors::SudokuError,
+};
+
+/// Builds the irregular regions of a jigsaw sudoku from a region map, in which each character
+/// names the region of the cell at that position.
+///
+/// Like the diagonal, the regions are turned into `HouseUnique::Custom` houses, and take the
+/// place of the boxes. Every region must have one cell per symbol and be orthogonally connected.
+pub(crate) fn get_jigsaw_houses(
+    regions: &str,
+    size: (usize, usize),
+) -> Result<Vec<House>, SudokuError> {
+    let (rows, cols) = size;
+    let lines: Vec<&str> = regions.lines().collect();
+    if lines.len() != rows {
+        return Err(SudokuError::ConstraintPredicateInvalid(format!(
+            "Jigsaw region map has {} lines, expected {rows} like the board",
+            lines.len()
+        )));
+    }
+    let mut houses: BTreeMap<char, House> = BTreeMap::new();
+    for (row, line) in lines.iter().enumerate() {
+        if line.chars().count() != cols {
+            return Err(SudokuError::ConstraintPredicateInvalid(format!(
+                "Jigsaw region map line {} {line:?} has {} cells, expected {cols}",
+                row + 1,
+                line.chars().count()
+            )));
+        }
+        for (col, region) in line.chars().enumerate() {
+            houses.entry(region).or_default().push(Cell { row, col });
+        }
+    }
+    let region_size = rows.max(cols);
+    for (region, house) in &houses {
+        if house.len() != region_size {
+            return Err(SudokuError::ConstraintPredicateInvalid(format!(
+                "Jigsaw region {region:?} has {} cells, expected {region_size}",
+                house.len()
+            )));
+        }
+        if !is_connected(house) {
+            return Err(SudokuError::ConstraintPredicateInvalid(format!(
+                "Jigsaw region {region:?} is not orthogonally connected"
+            )));
+        }
+    }
+    Ok(houses.into_values().collect())
+}
+
+fn is_connected(house: &House) -> bool {
+    let first = match house.first() {
+        Some(c) => *c,
+        None => return true,
+    };
+    let mut seen = HashSet::from([first]);
+    let mut stack = vec![first];
+    while let Some(cell) = stack.pop() {
+        for other in house {
+            let adjacent = cell.row.abs_diff(other.row) + cell.col.abs_diff(other.col) == 1;
+            if adjacent && seen.insert(*other) {
+                stack.push(*other);
+            }
+        }
+    }
+    seen.len() == house.len()
+}
@@ -1,20 +1,32 @@
-use std::{collections::HashSet, hash::Hash};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    rc::Rc,
+};
 
 use itertools::Itertools;
 use log::{debug, trace};
 
 use crate::{
     board::{
-        constraints::standard::House,
-        digit::Symbol,
+        constraints::standard::{share_house, House, HouseSet},
+        digit::{Candidates, Symbol},
+        solver::uniqueness::keeps_swap,
+        strategy::Strategy,
         sudoku::{Cell, DidUpdateGrid},
     },
     errors::SudokuError,
     Constraint, Sudoku,
 };
 
-/// This constraint is only responsible for making sure the numbers all add up.
+/// The weight logged for narrowing a cage down to the combinations making its marking.
+const CAGE_WEIGHT: f32 = 2.0;
+
+/// This constraint is only responsible for making sure the numbers in each cage work out
+/// to its marking (a sum, product, difference or quotient).
 /// Uniqueness is handled by a seperate `HouseUnique` constraint, for those Cells.
+/// Digits within a cage are distinct, unless the cage is marked as not unique.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct Killer {
     cages: Vec<Cage>,
@@ -25,17 +37,39 @@ impl Killer {
         debug!("Killer Created");
         Self { cages }
     }
+
+    pub(crate) fn get_cages(&self) -> &[Cage] {
+        &self.cages
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct Cage {
     pub(crate) cells: House,
     marking: KillerMarking,
+    /// Whether the digits in the cage must all differ.
+    unique: bool,
 }
 
 impl Cage {
-    pub(crate) fn new(cells: House, marking: KillerMarking) -> Self {
-        Self { cells, marking }
+    pub(crate) fn new(cells: House, marking: KillerMarking, unique: bool) -> Self {
+        Self {
+            cells,
+            marking,
+            unique,
+        }
+    }
+
+    pub(crate) fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// The total of the cage, if it has one.
+    pub(crate) fn get_sum(&self) -> Option<u32> {
+        match self.marking {
+            KillerMarking::Sum(sum) => Some(sum),
+            _ => None,
+        }
     }
 }
 
@@ -43,6 +77,11 @@ impl Cage {
 pub(crate) enum KillerMarking {
     None,
     Sum(u32),
+    Product(u32),
+    /// The largest digit minus all of the others.
+    Difference(u32),
+    /// The largest digit divided by all of the others.
+    Quotient(u32),
 }
 
 impl Constraint for Killer {
@@ -51,10 +90,14 @@ impl Constraint for Killer {
         sudoku: &mut Sudoku,
         cell: &Cell,
     ) -> Result<DidUpdateGrid, SudokuError> {
-        let cage = match self.cages.iter().find(|c| c.cells.contains(cell)) {
+        let cage = match self
+            .cages
+            .iter()
+            .find(|c| c.unique && c.cells.contains(cell))
+        {
             Some(c) => c,
             None => {
-                trace!("Cell {cell:?} is not part of any Killer Cage");
+                trace!("Cell {cell:?} is not part of any unique Killer Cage");
                 return Ok(false);
             }
         };
@@ -73,12 +116,38 @@ impl Constraint for Killer {
         self
     }
 
-    fn use_strategies(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
-        debug!("Killer Notify Update");
-        let mut did_update = false;
-        for cage in &self.cages {
-            did_update |= cage.notify_cage(sudoku)?;
-            if did_update {
+    fn allows_swap(&self, _sudoku: &Sudoku, rectangle: &[Cell; 4]) -> bool {
+        self.cages
+            .iter()
+            .all(|cage| keeps_swap(&cage.cells, rectangle))
+    }
+
+    fn is_violated(&self, sudoku: &Sudoku) -> bool {
+        self.cages.iter().any(|cage| cage.is_violated(sudoku))
+    }
+}
+
+/// Narrows each cage down to the combinations of digits making its marking.
+pub(crate) struct CageCombinations;
+
+impl Strategy for CageCombinations {
+    fn name(&self) -> &str {
+        "Cage Combinations"
+    }
+
+    fn weight(&self) -> f32 {
+        CAGE_WEIGHT
+    }
+
+    fn is_applicable(&self, sudoku: &Sudoku) -> bool {
+        sudoku.constraints.iter().any(|c| c.as_any().is::<Killer>())
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        debug!("Running Cage Combinations");
+        for cage in get_all_cages(sudoku) {
+            if cage.notify_cage(sudoku)? {
+                sudoku.note_regions(&[&cage.cells]);
                 return Ok(true);
             }
         }
@@ -86,22 +155,254 @@ impl Constraint for Killer {
     }
 }
 
+/// The cages of every killer constraint of the sudoku.
+pub(crate) fn get_all_cages(sudoku: &Sudoku) -> Vec<Cage> {
+    sudoku
+        .constraints
+        .iter()
+        .filter_map(|c| c.as_any().downcast_ref::<Killer>())
+        .flat_map(|k| k.get_cages().iter().cloned())
+        .collect()
+}
+
 impl Cage {
-    fn notify_cage(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+    pub(crate) fn notify_cage(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
         trace!("KillerCage Notify Update");
+        if !self.unique {
+            return self.notify_non_unique_cage(sudoku);
+        }
         let mut did_update = false;
         let possible_candidates = self.marking.get_possible_candidates(self, sudoku)?;
         let unsolved_cells: Vec<Cell> = self
             .cells
             .iter()
             .filter(|c| sudoku.get_cell(c).is_ok_and(|f| !f.is_solved()))
-            .map(|c| *c)
+            .copied()
             .collect();
         did_update |= sudoku.keep_candidates(unsolved_cells, &possible_candidates)?;
+        if let KillerMarking::Sum(sum) = self.marking {
+            did_update |= self.place_required_digits(sudoku, sum)?;
+        }
+        Ok(did_update)
+    }
+
+    /// A digit used by every combination adding up to `cage_sum` has to go in the cage,
+    /// so if only one cell can still hold it, it goes there.
+    fn place_required_digits(
+        &self,
+        sudoku: &mut Sudoku,
+        cage_sum: u32,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let combinations = self.get_sum_combinations(sudoku, cage_sum);
+        let required: Vec<Symbol> = match combinations.first() {
+            Some(first) => first
+                .iter()
+                .filter(|s| combinations.iter().all(|c| c.contains(s)))
+                .copied()
+                .collect(),
+            None => return Ok(false),
+        };
+        let mut did_update = false;
+        for symbol in required {
+            let holders: Vec<Cell> = self
+                .cells
+                .iter()
+                .filter(|c| {
+                    sudoku
+                        .get_cell(c)
+                        .is_ok_and(|d| !d.is_solved() && d.0.contains(&symbol))
+                })
+                .copied()
+                .collect();
+            if let [cell] = holders.as_slice() {
+                debug!("{symbol:?} is needed by every combination of {self:?}, only {cell:?} can hold it");
+                did_update |= sudoku.place_digit(cell, &symbol)?;
+            }
+        }
         Ok(did_update)
     }
-    fn get_sum_options(&self, sudoku: &mut Sudoku, cage_sum: u32) -> HashSet<Symbol> {
-        debug!("Getting Sum Options for Cage: {self:?} with sum {cage_sum}");
+
+    /// Digits may repeat in the cage, so each cell keeps its own candidates
+    /// rather than sharing one pool of digits with the rest of the cage.
+    fn notify_non_unique_cage(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        let houses = sudoku.get_houses();
+        let per_cell = match self.marking {
+            KillerMarking::None => return Ok(false),
+            KillerMarking::Sum(sum) => {
+                self.get_sum_options(sudoku, &HashSet::from([sum]))?
+                    .per_cell
+            }
+            _ => self.get_multiset_options(sudoku, &houses)?,
+        };
+        let mut did_update = false;
+        for (cell, keep) in self.cells.iter().zip(per_cell) {
+            if sudoku.get_cell(cell)?.is_solved() {
+                continue;
+            }
+            let keep: Vec<Symbol> = keep.into_iter().collect();
+            did_update |= sudoku.keep_candidates([*cell], &keep)?;
+        }
+        Ok(did_update)
+    }
+
+    /// Tries every way of filling the cage from each cell's candidates, where digits
+    /// only have to differ between cells sharing one of `houses`.
+    fn get_multiset_options(
+        &self,
+        sudoku: &Sudoku,
+        houses: &HouseSet,
+    ) -> Result<Vec<HashSet<Symbol>>, SudokuError> {
+        let options = self
+            .cells
+            .iter()
+            .map(|c| Ok(sudoku.get_cell(c)?.0.clone()))
+            .collect::<Result<Vec<_>, SudokuError>>()?;
+        let mut keep = vec![HashSet::new(); self.cells.len()];
+        self.search_multisets(sudoku, &options, houses, &mut vec![], &mut keep);
+        debug!("Valid Digits per cell for {self:?}: {keep:?}");
+        Ok(keep)
+    }
+
+    fn search_multisets(
+        &self,
+        sudoku: &Sudoku,
+        options: &[Candidates],
+        houses: &HouseSet,
+        chosen: &mut Vec<Symbol>,
+        keep: &mut [HashSet<Symbol>],
+    ) {
+        let i = chosen.len();
+        if i == options.len() {
+            let values: Vec<u32> = chosen
+                .iter()
+                .map(|s| sudoku.get_value(s).unwrap_or(0))
+                .collect();
+            if self.marking.holds(&values) {
+                for (k, symbol) in chosen.iter().enumerate() {
+                    keep[k].insert(*symbol);
+                }
+            }
+            return;
+        }
+        for symbol in &options[i] {
+            let repeats = chosen
+                .iter()
+                .enumerate()
+                .any(|(j, s)| s == symbol && share_house(houses, &self.cells[i], &self.cells[j]));
+            if repeats {
+                continue;
+            }
+            chosen.push(*symbol);
+            self.search_multisets(sudoku, options, houses, chosen, keep);
+            chosen.pop();
+        }
+    }
+
+    /// Finds the symbols each cell can take so that the cage adds up to one of `targets`.
+    ///
+    /// The digits of a unique cage are distinct, so they come from the sudoku's table of sum
+    /// combinations. Otherwise two cells may repeat a digit unless they share a house, and
+    /// rather than listing every combination, a single witness is searched for per target and
+    /// per candidate, which keeps long arrows cheap.
+    pub(crate) fn get_sum_options(
+        &self,
+        sudoku: &Sudoku,
+        targets: &HashSet<u32>,
+    ) -> Result<SumOptions, SudokuError> {
+        debug!("Getting Sum Options for Cage: {self:?} with sums {targets:?}");
+        let found = match self.unique {
+            true => self.get_unique_sum_options(sudoku, targets)?,
+            false => self.get_repeated_sum_options(sudoku, targets)?,
+        };
+        trace!("Sum options for {self:?}: {:?}", found.totals);
+        Ok(found)
+    }
+
+    fn get_unique_sum_options(
+        &self,
+        sudoku: &Sudoku,
+        targets: &HashSet<u32>,
+    ) -> Result<SumOptions, SudokuError> {
+        let mut found = SumOptions::new(self.cells.len());
+        for &target in targets {
+            let combinations = self.get_sum_combinations(sudoku, target);
+            if combinations.is_empty() {
+                continue;
+            }
+            found.totals.insert(target);
+            let symbols: HashSet<Symbol> = combinations.into_iter().flatten().collect();
+            for (keep, cell) in found.per_cell.iter_mut().zip(&self.cells) {
+                let digit = sudoku.get_cell(cell)?;
+                match digit.try_get_solved() {
+                    Some(symbol) => {
+                        keep.insert(*symbol);
+                    }
+                    None => keep.extend(digit.0.iter().filter(|s| symbols.contains(s))),
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    fn get_repeated_sum_options(
+        &self,
+        sudoku: &Sudoku,
+        targets: &HashSet<u32>,
+    ) -> Result<SumOptions, SudokuError> {
+        let houses = sudoku.get_houses();
+        let options = self
+            .cells
+            .iter()
+            .map(|c| {
+                let digit = sudoku.get_cell(c)?;
+                Ok(digit
+                    .0
+                    .iter()
+                    .filter_map(|s| sudoku.get_value(s).map(|v| (*s, v)))
+                    .collect())
+            })
+            .collect::<Result<Vec<Vec<(Symbol, u32)>>, SudokuError>>()?;
+        let mut search = SumSearch {
+            options,
+            must_differ: self
+                .cells
+                .iter()
+                .map(|a| {
+                    self.cells
+                        .iter()
+                        .map(|b| share_house(&houses, a, b))
+                        .collect()
+                })
+                .collect(),
+            chosen: vec![],
+        };
+        let mut found = SumOptions::new(self.cells.len());
+        for target in targets.iter().copied().sorted() {
+            if let Some(witness) = search.find_witness(target) {
+                found.record(target, witness);
+            }
+        }
+        for i in 0..self.cells.len() {
+            for (symbol, value) in search.options[i].clone() {
+                if found.per_cell[i].contains(&symbol) {
+                    continue;
+                }
+                let all_options = std::mem::replace(&mut search.options[i], vec![(symbol, value)]);
+                for target in found.totals.clone().into_iter().sorted() {
+                    if let Some(witness) = search.find_witness(target) {
+                        found.record(target, witness);
+                        break;
+                    }
+                }
+                search.options[i] = all_options;
+            }
+        }
+        Ok(found)
+    }
+
+    /// The combinations of distinct digits the unsolved cells could take to make `cage_sum`,
+    /// looked up in the sudoku's combination table.
+    fn get_sum_combinations(&self, sudoku: &Sudoku, cage_sum: u32) -> Vec<Vec<Symbol>> {
         let candidates: HashSet<&Symbol> = self
             .cells
             .iter()
@@ -119,24 +420,113 @@ impl Cage {
             .filter_map(|cell| sudoku.get_cell(cell).ok().and_then(|c| c.try_get_solved()))
             .collect::<Vec<_>>();
         let num_options = self.cells.len() - solved.len();
-        let cage_sum_without_placed = cage_sum
-            - solved
-                .iter()
-                .map(|s| s.get_number().unwrap_or(0))
-                .sum::<u32>();
+        let placed_sum = solved
+            .iter()
+            .map(|s| sudoku.get_value(s).unwrap_or(0))
+            .sum::<u32>();
+        let cage_sum_without_placed = match cage_sum.checked_sub(placed_sum) {
+            Some(v) => v,
+            None => {
+                debug!("Placed digits {solved:?} already exceed cage sum {cage_sum}");
+                return vec![];
+            }
+        };
         trace!("Need to come up with {num_options} options for cage sum {cage_sum}, reduced to {cage_sum_without_placed}. Candidate Cells: {candidates:?}");
+        sudoku
+            .sum_combinations
+            .get(num_options, cage_sum_without_placed)
+            .iter()
+            .filter(|digits| {
+                digits
+                    .iter()
+                    .all(|s| candidates.contains(s) && !solved.contains(&s))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Like `get_sum_options`, but the digits have to multiply up to `cage_product`.
+    fn get_product_options(&self, sudoku: &Sudoku, cage_product: u32) -> HashSet<Symbol> {
+        debug!("Getting Product Options for Cage: {self:?} with product {cage_product}");
+        let placed = self.get_placed_values(sudoku);
+        let placed_product = placed.iter().map(|&v| v as u64).product::<u64>();
+        if placed_product != 0 && !(cage_product as u64).is_multiple_of(placed_product) {
+            debug!("Placed digits {placed:?} do not divide cage product {cage_product}");
+            return HashSet::new();
+        }
+        self.get_combination_options(sudoku, &placed, &KillerMarking::Product(cage_product))
+    }
+
+    /// Keeps the digits which can make the largest digit minus the others equal `cage_difference`.
+    fn get_difference_options(&self, sudoku: &Sudoku, cage_difference: u32) -> HashSet<Symbol> {
+        debug!("Getting Difference Options for Cage: {self:?} with difference {cage_difference}");
+        let placed = self.get_placed_values(sudoku);
+        self.get_combination_options(sudoku, &placed, &KillerMarking::Difference(cage_difference))
+    }
+
+    /// Keeps the digits which can make the largest digit divided by the others equal `cage_quotient`.
+    fn get_quotient_options(&self, sudoku: &Sudoku, cage_quotient: u32) -> HashSet<Symbol> {
+        debug!("Getting Quotient Options for Cage: {self:?} with quotient {cage_quotient}");
+        let placed = self.get_placed_values(sudoku);
+        self.get_combination_options(sudoku, &placed, &KillerMarking::Quotient(cage_quotient))
+    }
+
+    fn get_placed_values(&self, sudoku: &Sudoku) -> Vec<u32> {
+        self.cells
+            .iter()
+            .filter_map(|cell| sudoku.get_cell(cell).ok().and_then(|c| c.try_get_solved()))
+            .map(|s| sudoku.get_value(s).unwrap_or(0))
+            .collect()
+    }
+
+    /// Tries every combination of distinct candidates for the unsolved cells, keeping the
+    /// candidates of each combination which, along with the `placed` values, holds for `marking`.
+    fn get_combination_options(
+        &self,
+        sudoku: &Sudoku,
+        placed: &[u32],
+        marking: &KillerMarking,
+    ) -> HashSet<Symbol> {
+        let candidates: HashSet<&Symbol> = self
+            .cells
+            .iter()
+            .filter_map(|cell| {
+                sudoku
+                    .get_cell(cell)
+                    .ok()
+                    .and_then(|c| c.try_get_candidates())
+            })
+            .flatten()
+            .collect();
+        let num_options = self.cells.len() - placed.len();
         let mut keep_digits = HashSet::new();
-        // Generate num_options of candidates.
         for digits in candidates.iter().combinations(num_options) {
-            trace!("Checking digits: {digits:?}");
-            if digits.iter().filter_map(|f| f.get_number()).sum::<u32>() == cage_sum_without_placed
-            {
+            let values: Vec<u32> = digits.iter().filter_map(|f| sudoku.get_value(f)).collect();
+            if marking.holds(&[placed, values.as_slice()].concat()) {
                 keep_digits.extend(digits.iter().map(|&&s| s));
             }
         }
         debug!("Valid Digits for {keep_digits:?}");
         keep_digits
     }
+
+    /// A cage is violated once every cell is placed and the marking does not hold.
+    fn is_violated(&self, sudoku: &Sudoku) -> bool {
+        let placed: Option<Vec<&Symbol>> = self
+            .cells
+            .iter()
+            .map(|cell| sudoku.get_cell(cell).ok().and_then(|c| c.try_get_solved()))
+            .collect();
+        let placed = match placed {
+            Some(p) => p,
+            None => return false,
+        };
+        let values: Vec<u32> = placed
+            .iter()
+            .map(|s| sudoku.get_value(s).unwrap_or(0))
+            .collect();
+        !self.marking.holds(&values)
+    }
 }
 
 impl KillerMarking {
@@ -145,13 +535,175 @@ impl KillerMarking {
     fn get_possible_candidates(
         &self,
         cage: &Cage,
-        sudoku: &mut Sudoku,
+        sudoku: &Sudoku,
     ) -> Result<Vec<Symbol>, SudokuError> {
         let ok = match self {
             KillerMarking::None => sudoku.valid_symbols.clone(),
-            KillerMarking::Sum(sum) => cage.get_sum_options(sudoku, *sum),
+            KillerMarking::Sum(sum) => {
+                let options = cage.get_sum_options(sudoku, &HashSet::from([*sum]))?;
+                // The placed digits are already accounted for in the sum.
+                cage.cells
+                    .iter()
+                    .zip(options.per_cell)
+                    .filter(|(c, _)| sudoku.get_cell(c).is_ok_and(|d| !d.is_solved()))
+                    .flat_map(|(_, keep)| keep)
+                    .collect()
+            }
+            KillerMarking::Product(product) => cage.get_product_options(sudoku, *product),
+            KillerMarking::Difference(difference) => {
+                cage.get_difference_options(sudoku, *difference)
+            }
+            KillerMarking::Quotient(quotient) => cage.get_quotient_options(sudoku, *quotient),
         };
         debug!("{self:?} Possible Candidates: {ok:?}");
         Ok(ok.iter().cloned().collect())
     }
+
+    /// Whether a fully placed cage with these values satisfies the marking.
+    fn holds(&self, values: &[u32]) -> bool {
+        let largest = values.iter().copied().max().unwrap_or(0);
+        // Everything but one copy of the largest value.
+        let rest = || {
+            let index = values.iter().position(|&v| v == largest);
+            values
+                .iter()
+                .enumerate()
+                .filter(move |(i, _)| Some(*i) != index)
+                .map(|(_, &v)| v)
+        };
+        match self {
+            KillerMarking::None => true,
+            KillerMarking::Sum(sum) => values.iter().sum::<u32>() == *sum,
+            KillerMarking::Product(product) => {
+                values.iter().map(|&v| v as u64).product::<u64>() == *product as u64
+            }
+            KillerMarking::Difference(difference) => {
+                largest.checked_sub(rest().sum::<u32>()) == Some(*difference)
+            }
+            KillerMarking::Quotient(quotient) => {
+                let divisor = rest().map(|v| v as u64).product::<u64>();
+                divisor != 0 && largest as u64 == divisor * *quotient as u64
+            }
+        }
+    }
+}
+
+type Combinations = Rc<Vec<Vec<Symbol>>>;
+
+/// Every set of distinct symbols of a given size adding up to a given total.
+///
+/// Each entry is built the first time it is asked for, and the table is shared
+/// between a sudoku and all of its clones, since they have the same symbols.
+#[derive(Debug, Default)]
+pub(crate) struct SumCombinations {
+    symbols: Vec<(Symbol, u32)>,
+    table: RefCell<HashMap<(usize, u32), Combinations>>,
+}
+
+impl SumCombinations {
+    pub(crate) fn new(symbol_values: &HashMap<Symbol, u32>) -> Self {
+        let symbols = symbol_values
+            .iter()
+            .map(|(s, v)| (*s, *v))
+            .sorted_by_key(|(_, v)| *v)
+            .collect();
+        Self {
+            symbols,
+            table: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The combinations of `size` distinct symbols adding up to `total`.
+    pub(crate) fn get(&self, size: usize, total: u32) -> Combinations {
+        self.table
+            .borrow_mut()
+            .entry((size, total))
+            .or_insert_with(|| {
+                trace!("Building combinations of {size} symbols adding up to {total}");
+                Rc::new(
+                    self.symbols
+                        .iter()
+                        .combinations(size)
+                        .filter(|c| c.iter().map(|(_, v)| v).sum::<u32>() == total)
+                        .map(|c| c.into_iter().map(|(s, _)| *s).collect())
+                        .collect(),
+                )
+            })
+            .clone()
+    }
+}
+
+/// The symbols each cell of a cage can take so that it adds up to one of the targets.
+pub(crate) struct SumOptions {
+    /// For each cell (in order), the candidates used in at least one way of making a target.
+    pub(crate) per_cell: Vec<HashSet<Symbol>>,
+    /// Every target that can be made.
+    pub(crate) totals: HashSet<u32>,
+}
+
+impl SumOptions {
+    fn new(num_cells: usize) -> Self {
+        Self {
+            per_cell: vec![HashSet::new(); num_cells],
+            totals: HashSet::new(),
+        }
+    }
+
+    fn record(&mut self, total: u32, witness: Vec<Symbol>) {
+        self.totals.insert(total);
+        for (cell, symbol) in witness.into_iter().enumerate() {
+            self.per_cell[cell].insert(symbol);
+        }
+    }
+}
+
+struct SumSearch {
+    options: Vec<Vec<(Symbol, u32)>>,
+    must_differ: Vec<Vec<bool>>,
+    chosen: Vec<Symbol>,
+}
+
+impl SumSearch {
+    /// One way of filling every cell that adds up to exactly `target`, if there is one.
+    fn find_witness(&mut self, target: u32) -> Option<Vec<Symbol>> {
+        self.chosen.clear();
+        match self.search(target) {
+            true => Some(self.chosen.clone()),
+            false => None,
+        }
+    }
+
+    fn search(&mut self, remaining: u32) -> bool {
+        let i = self.chosen.len();
+        if i == self.options.len() {
+            return remaining == 0;
+        }
+        let rest = &self.options[i + 1..];
+        let min_rest: u32 = rest
+            .iter()
+            .filter_map(|o| o.iter().map(|(_, v)| *v).min())
+            .sum();
+        let max_rest: u32 = rest
+            .iter()
+            .filter_map(|o| o.iter().map(|(_, v)| *v).max())
+            .sum();
+        for k in 0..self.options[i].len() {
+            let (symbol, value) = self.options[i][k];
+            let fits = value + min_rest <= remaining && remaining <= value + max_rest;
+            let repeats = self
+                .chosen
+                .iter()
+                .enumerate()
+                .any(|(j, s)| *s == symbol && self.must_differ[i][j]);
+            if !fits || repeats {
+                continue;
+            }
+            self.chosen.push(symbol);
+            if self.search(remaining - value) {
+                return true;
+            }
+            self.chosen.pop();
+        }
+        false
+    }
 }
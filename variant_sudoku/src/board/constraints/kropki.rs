@@ -0,0 +1,259 @@
+use std::any::Any;
+
+use itertools::Itertools;
+use log::{debug, trace};
+
+use crate::{
+    board::{
+        constraints::WeakLink,
+        digit::Symbol,
+        strategy::Strategy,
+        sudoku::{Cell, DidUpdateGrid},
+    },
+    errors::SudokuError,
+    Constraint, Sudoku,
+};
+
+/// The weight logged for ruling out digits with no partner across a dot.
+const KROPKI_WEIGHT: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum KropkiColor {
+    /// The digits on either side of the dot are in a 1:2 ratio.
+    Black,
+    /// The digits on either side of the dot are consecutive.
+    White,
+}
+
+impl KropkiColor {
+    fn holds(&self, sudoku: &Sudoku, a: &Symbol, b: &Symbol) -> bool {
+        let (a, b) = match (sudoku.get_value(a), sudoku.get_value(b)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return false,
+        };
+        match self {
+            KropkiColor::Black => a == 2 * b || b == 2 * a,
+            KropkiColor::White => a.abs_diff(b) == 1,
+        }
+    }
+}
+
+/// Dots of a single colour between orthogonally adjacent cells.
+///
+/// With `negative` set, every dot of this colour is given, so two adjacent cells without any
+/// dot between them (of either colour) cannot satisfy this colour's relation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Kropki {
+    color: KropkiColor,
+    dots: Vec<[Cell; 2]>,
+    negative: bool,
+}
+
+impl Kropki {
+    pub(crate) fn new(color: KropkiColor, dots: Vec<[Cell; 2]>, negative: bool) -> Self {
+        debug!("{color:?} Kropki Created");
+        Self {
+            color,
+            dots,
+            negative,
+        }
+    }
+
+    fn has_dot(&self, a: &Cell, b: &Cell) -> bool {
+        self.dots
+            .iter()
+            .any(|[x, y]| (x == a && y == b) || (x == b && y == a))
+    }
+
+    /// Returns the cells paired with `cell`, and whether the relation must hold for that pair.
+    fn get_pairs(&self, sudoku: &Sudoku, cell: &Cell) -> Vec<(Cell, bool)> {
+        let mut pairs: Vec<(Cell, bool)> = self
+            .dots
+            .iter()
+            .filter_map(|[a, b]| match cell {
+                c if c == a => Some((*b, true)),
+                c if c == b => Some((*a, true)),
+                _ => None,
+            })
+            .collect();
+        if self.negative {
+            pairs.extend(
+                get_orthogonal_neighbours(sudoku, cell)
+                    .into_iter()
+                    .filter(|n| !has_any_dot(sudoku, cell, n))
+                    .map(|n| (n, false)),
+            );
+        }
+        pairs
+    }
+
+    /// Removes candidates from `to` which have no partner in `from` that satisfies the pair.
+    fn prune_pair(
+        &self,
+        sudoku: &mut Sudoku,
+        from: &Cell,
+        to: &Cell,
+        must_hold: bool,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let from_candidates = sudoku.get_cell(from)?.0.clone();
+        let to_candidates = sudoku.get_cell(to)?.0.clone();
+        let no_partner: Vec<Symbol> = to_candidates
+            .into_iter()
+            .filter(|s| {
+                !from_candidates
+                    .iter()
+                    .any(|f| self.color.holds(sudoku, s, f) == must_hold)
+            })
+            .collect();
+        let mut did_update = false;
+        for symbol in &no_partner {
+            trace!(
+                "{:?} Kropki removing {symbol:?} from {to:?}, no partner in {from:?}",
+                self.color
+            );
+            did_update |= sudoku.remove_candidate(to, symbol)?;
+        }
+        Ok(did_update)
+    }
+}
+
+impl Constraint for Kropki {
+    fn notify_update(
+        &self,
+        sudoku: &mut Sudoku,
+        cell: &Cell,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let mut did_update = false;
+        for (other, must_hold) in self.get_pairs(sudoku, cell) {
+            did_update |= self.prune_pair(sudoku, cell, &other, must_hold)?;
+        }
+        Ok(did_update)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_weak_links(&self, sudoku: &Sudoku) -> Vec<WeakLink> {
+        let mut links = vec![];
+        for (cell, digit) in sudoku.indexed_iter() {
+            for (other, must_hold) in self.get_pairs(sudoku, &cell) {
+                let other_candidates = match sudoku.get_cell(&other) {
+                    Ok(d) => &d.0,
+                    Err(_) => continue,
+                };
+                for (a, b) in digit.0.iter().cartesian_product(other_candidates) {
+                    if self.color.holds(sudoku, a, b) != must_hold {
+                        links.push(((cell, *a), (other, *b)));
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    fn allows_swap(&self, _sudoku: &Sudoku, rectangle: &[Cell; 4]) -> bool {
+        !self.negative && !self.dots.iter().flatten().any(|c| rectangle.contains(c))
+    }
+
+    fn is_violated(&self, sudoku: &Sudoku) -> bool {
+        sudoku.indexed_iter().any(|(cell, digit)| {
+            let symbol = match digit.try_get_solved() {
+                Some(s) => s,
+                None => return false,
+            };
+            self.get_pairs(sudoku, &cell)
+                .iter()
+                .any(|(other, must_hold)| {
+                    sudoku
+                        .get_cell(other)
+                        .ok()
+                        .and_then(|d| d.try_get_solved())
+                        .is_some_and(|o| self.color.holds(sudoku, symbol, o) != *must_hold)
+                })
+        })
+    }
+}
+
+/// Rules out digits with no partner across a dot, for the dots of either colour.
+pub(crate) struct KropkiDot;
+
+impl Strategy for KropkiDot {
+    fn name(&self) -> &str {
+        "Kropki Dot"
+    }
+
+    fn weight(&self) -> f32 {
+        KROPKI_WEIGHT
+    }
+
+    fn is_applicable(&self, sudoku: &Sudoku) -> bool {
+        sudoku.constraints.iter().any(|c| c.as_any().is::<Kropki>())
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        debug!("Running Kropki Dot");
+        let kropkis: Vec<Kropki> = sudoku
+            .constraints
+            .iter()
+            .filter_map(|c| c.as_any().downcast_ref::<Kropki>())
+            .cloned()
+            .collect();
+        let (rows, cols) = sudoku.size();
+        for kropki in &kropkis {
+            for row in 0..rows {
+                for col in 0..cols {
+                    let cell = Cell { row, col };
+                    if kropki.notify_update(sudoku, &cell)? {
+                        let pairs = kropki.get_pairs(sudoku, &cell);
+                        let region: Vec<Cell> = std::iter::once(cell)
+                            .chain(pairs.into_iter().map(|(other, _)| other))
+                            .collect();
+                        sudoku.note_regions(&[region]);
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn get_orthogonal_neighbours(sudoku: &Sudoku, cell: &Cell) -> Vec<Cell> {
+    let (rows, cols) = sudoku.size();
+    let mut neighbours = vec![];
+    if cell.row > 0 {
+        neighbours.push(Cell {
+            row: cell.row - 1,
+            col: cell.col,
+        });
+    }
+    if cell.row + 1 < rows {
+        neighbours.push(Cell {
+            row: cell.row + 1,
+            col: cell.col,
+        });
+    }
+    if cell.col > 0 {
+        neighbours.push(Cell {
+            row: cell.row,
+            col: cell.col - 1,
+        });
+    }
+    if cell.col + 1 < cols {
+        neighbours.push(Cell {
+            row: cell.row,
+            col: cell.col + 1,
+        });
+    }
+    neighbours
+}
+
+/// Whether any Kropki constraint of the sudoku (black or white) has a dot between the two cells.
+fn has_any_dot(sudoku: &Sudoku, a: &Cell, b: &Cell) -> bool {
+    sudoku
+        .constraints
+        .iter()
+        .filter_map(|c| c.as_any().downcast_ref::<Kropki>())
+        .any(|k| k.has_dot(a, b))
+}
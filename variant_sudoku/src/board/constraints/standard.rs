@@ -1,24 +1,30 @@
-use std::{any::Any, collections::HashSet};
+use std::{
+    any::Any,
+    collections::{BTreeSet, HashSet},
+};
 
 use log::trace;
 
 use crate::{
     board::{
-        digit::Symbol,
-        solver::house::HOUSE_STRATEGIES,
+        digit::{Candidates, Symbol},
+        solver::uniqueness::keeps_swap,
         sudoku::{Cell, DidUpdateGrid},
     },
     errors::SudokuError,
     Constraint, Sudoku,
 };
 
-pub(crate) type HouseSet = HashSet<House>;
+/// Houses kept in order of their cells, so strategies find the same deduction on every run.
+pub(crate) type HouseSet = BTreeSet<House>;
+/// The (rows, cols) of a single box.
+pub(crate) type BoxSize = (usize, usize);
 
 #[allow(dead_code)]
 pub(crate) enum HouseUnique {
     Row,
     Col,
-    Box,
+    Box(BoxSize),
     Custom(Vec<House>),
 }
 
@@ -28,30 +34,13 @@ impl HouseUnique {
         match self {
             HouseUnique::Row => get_row_houses(sudoku),
             HouseUnique::Col => get_col_houses(sudoku),
-            HouseUnique::Box => get_box_houses(sudoku),
+            HouseUnique::Box(box_size) => get_box_houses(sudoku, *box_size),
             HouseUnique::Custom(cells) => cells.to_vec(),
         }
     }
 }
 
 impl Constraint for HouseUnique {
-    fn use_strategies(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
-        // Get all houses of the sudoku.
-        let houses: HouseSet = sudoku
-            .constraints
-            .iter()
-            .filter_map(|f| f.as_any().downcast_ref::<HouseUnique>())
-            .flat_map(|f| f.get_houses(sudoku))
-            .collect();
-        for (strategy, _) in HOUSE_STRATEGIES {
-            let did_update = strategy(sudoku, &houses)?;
-            if did_update {
-                return Ok(true);
-            }
-        }
-        Ok(false)
-    }
-
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -80,39 +69,98 @@ impl Constraint for HouseUnique {
         }
         Ok(did_update)
     }
+
+    fn allows_swap(&self, sudoku: &Sudoku, rectangle: &[Cell; 4]) -> bool {
+        self.get_houses(sudoku)
+            .iter()
+            .all(|house| keeps_swap(house, rectangle))
+    }
+
+    fn is_violated(&self, sudoku: &Sudoku) -> bool {
+        self.get_houses(sudoku).iter().any(|house| {
+            let mut seen = HashSet::new();
+            house
+                .iter()
+                .filter_map(|c| sudoku.get_cell(c).ok().and_then(|d| d.try_get_solved()))
+                .any(|s| !seen.insert(*s))
+        })
+    }
+}
+
+/// Every house of the sudoku, across all of its house constraints.
+pub(crate) fn get_all_houses(sudoku: &Sudoku) -> HouseSet {
+    sudoku
+        .constraints
+        .iter()
+        .filter_map(|c| c.as_any().downcast_ref::<HouseUnique>())
+        .flat_map(|h| h.get_houses(sudoku))
+        .collect()
 }
 
 fn get_row_houses(sudoku: &Sudoku) -> Vec<House> {
     let (rows, cols) = sudoku.size();
-    (0..cols)
-        .map(|row| (0..rows).map(|col| Cell { row, col }).collect())
+    (0..rows)
+        .map(|row| (0..cols).map(|col| Cell { row, col }).collect())
         .collect()
 }
 
 fn get_col_houses(sudoku: &Sudoku) -> Vec<House> {
     let (rows, cols) = sudoku.size();
-    (0..rows)
-        .map(|col| (0..cols).map(|row| Cell { row, col }).collect())
+    (0..cols)
+        .map(|col| (0..rows).map(|row| Cell { row, col }).collect())
         .collect()
 }
 
-pub(crate) fn get_box_size(size: (usize, usize)) -> Result<(usize, usize), SudokuError> {
-    let ok = match size {
-        (9, 9) => (3, 3),
-        (4, 4) => (2, 2),
-        (6, 6) => (2, 3), //2 rows, 3 cols
-        v => {
-            return Err(SudokuError::UnsupportedConstraint(format!(
-                "Invalid BoxUnique with grid of size {v:?}"
-            )))
-        }
-    };
-    Ok(ok)
+/// Derives the box shape of a grid from the most-square factorization of its symbol count.
+///
+/// For instance an 8x8 grid has 2x4 boxes (2 rows, 4 cols), a 12x12 grid has 3x4 boxes,
+/// and a 6x9 fragment of a larger puzzle has 3x3 boxes.
+pub(crate) fn get_box_size(size: (usize, usize)) -> Result<BoxSize, SudokuError> {
+    let (rows, cols) = size;
+    let n = rows.max(cols);
+    (2..=n.isqrt())
+        .rev()
+        .filter(|r| n % r == 0)
+        .flat_map(|r| [(r, n / r), (n / r, r)])
+        .find(|&(box_rows, box_cols)| rows % box_rows == 0 && cols % box_cols == 0)
+        .ok_or_else(|| {
+            SudokuError::UnsupportedConstraint(format!(
+                "Invalid BoxUnique with grid of size {size:?}, it has no box shape"
+            ))
+        })
+}
+
+/// A house is complete when it has a cell for every symbol, so each symbol must appear in it.
+///
+/// Only complete houses can be used for hidden singles and the like. In a non-square grid
+/// the shorter lines only guarantee uniqueness.
+pub(crate) fn is_complete_house(sudoku: &Sudoku, house: &House) -> bool {
+    house.len() == sudoku.valid_symbols.len()
+}
+
+/// Checks that an explicit box shape tiles the grid, with one cell per symbol in each box.
+pub(crate) fn validate_box_size(
+    size: (usize, usize),
+    box_size: BoxSize,
+) -> Result<BoxSize, SudokuError> {
+    let (rows, cols) = size;
+    let (box_rows, box_cols) = box_size;
+    let tiles = box_rows != 0
+        && box_cols != 0
+        && rows % box_rows == 0
+        && cols % box_cols == 0
+        && box_rows * box_cols == rows.max(cols);
+    if !tiles {
+        return Err(SudokuError::ConstraintPredicateInvalid(format!(
+            "Box of size {box_size:?} does not tile a grid of size {size:?}"
+        )));
+    }
+    Ok(box_size)
 }
 
-fn get_box_houses(sudoku: &Sudoku) -> Vec<House> {
+fn get_box_houses(sudoku: &Sudoku, box_size: BoxSize) -> Vec<House> {
     let mut houses = vec![];
-    let (box_row_size, box_col_size) = get_box_size(sudoku.size()).unwrap();
+    let (box_row_size, box_col_size) = box_size;
     let (rows, cols) = sudoku.size();
     let num_box_rows = rows / box_row_size;
     let num_box_cols = cols / box_col_size;
@@ -132,20 +180,26 @@ fn get_box_houses(sudoku: &Sudoku) -> Vec<House> {
     houses
 }
 
+/// Every candidate of the unsolved cells in the house.
 pub(crate) fn get_house_candidates(
     sudoku: &Sudoku,
     house: &House,
-) -> Result<HashSet<Symbol>, SudokuError> {
-    let mut candidates = HashSet::new();
+) -> Result<Candidates, SudokuError> {
+    let mut candidates = sudoku.all_candidates.with_symbols([]);
     for cell in house {
         let digit = sudoku.get_cell(cell)?;
         if let Some(c) = digit.try_get_candidates() {
-            candidates.extend(c.iter().cloned());
+            candidates = candidates.union(c);
         }
     }
     Ok(candidates)
 }
 
+/// Whether two different cells share one of the houses, and so cannot hold the same symbol.
+pub(crate) fn share_house(houses: &HouseSet, a: &Cell, b: &Cell) -> bool {
+    a != b && houses.iter().any(|h| h.contains(a) && h.contains(b))
+}
+
 pub(crate) fn get_cells_in_house(
     sudoku: &Sudoku,
     house: &House,
@@ -0,0 +1,378 @@
+use std::{any::Any, collections::HashSet};
+
+use itertools::Itertools;
+use log::{debug, trace};
+
+use crate::{
+    board::{
+        constraints::{
+            killer::{get_all_cages, Cage, Killer},
+            standard::{share_house, House, HouseSet},
+            WeakLink,
+        },
+        digit::{Candidates, Symbol},
+        strategy::Strategy,
+        sudoku::{Cell, DidUpdateGrid},
+    },
+    errors::SudokuError,
+    Constraint, Sudoku,
+};
+
+/// The weight logged for tightening the bounds along a thermometer.
+const THERMO_WEIGHT: f32 = 2.0;
+
+/// The weight logged for solving a killer cage together with the thermometers through it.
+const THERMO_CAGE_WEIGHT: f32 = 3.0;
+
+/// Digits along each thermometer strictly increase from the bulb (the first cell) to the tip.
+///
+/// With `slow` set, neighbouring cells on the thermometer may also be equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Thermo {
+    thermos: Vec<House>,
+    slow: bool,
+}
+
+impl Thermo {
+    pub(crate) fn new(thermos: Vec<House>, slow: bool) -> Self {
+        debug!("Thermo Created");
+        Self { thermos, slow }
+    }
+
+    /// Whether `a` followed by `b` is a valid step along a thermometer.
+    fn in_order(&self, a: u32, b: u32) -> bool {
+        match self.slow {
+            true => a <= b,
+            false => a < b,
+        }
+    }
+
+    /// The smallest gap between two cells of a thermometer, `cells_between` apart.
+    ///
+    /// On a slow thermometer cells may only repeat if they do not share a house.
+    fn min_gap(&self, houses: &HouseSet, from: &Cell, to: &Cell, cells_between: usize) -> u32 {
+        match self.slow {
+            false => cells_between as u32,
+            true if share_house(houses, from, to) => 1,
+            true => 0,
+        }
+    }
+
+    /// Removes candidates that cannot fit between the bulb and the tip of the thermometer.
+    ///
+    /// Every cell needs a candidate large enough to follow the smallest options of the cells
+    /// before it, and small enough to precede the largest options of the cells after it.
+    fn tighten_bounds(
+        &self,
+        sudoku: &mut Sudoku,
+        thermo: &House,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let houses = match self.slow {
+            true => sudoku.get_houses(),
+            false => HouseSet::new(),
+        };
+        let candidates = thermo
+            .iter()
+            .map(|c| Ok(sudoku.get_cell(c)?.0.clone()))
+            .collect::<Result<Vec<_>, SudokuError>>()?;
+        let values: Vec<Vec<(Symbol, u32)>> =
+            candidates.iter().map(|c| get_values(sudoku, c)).collect();
+        let mut lower: Vec<u32> = vec![0; thermo.len()];
+        let mut upper: Vec<u32> = vec![u32::MAX; thermo.len()];
+        for i in 0..thermo.len() {
+            let bound = (0..i)
+                .map(|j| {
+                    lower[j].saturating_add(self.min_gap(&houses, &thermo[j], &thermo[i], i - j))
+                })
+                .max()
+                .unwrap_or(0);
+            lower[i] = first_at_least(&values[i], bound);
+        }
+        for i in (0..thermo.len()).rev() {
+            let bound = (i + 1..thermo.len())
+                .map(|j| {
+                    upper[j].saturating_sub(self.min_gap(&houses, &thermo[i], &thermo[j], j - i))
+                })
+                .min()
+                .unwrap_or(u32::MAX);
+            upper[i] = last_at_most(&values[i], bound);
+        }
+        let mut did_update = false;
+        for (i, cell) in thermo.iter().enumerate() {
+            for symbol in &candidates[i] {
+                if sudoku
+                    .get_value(symbol)
+                    .is_some_and(|v| lower[i] <= v && v <= upper[i])
+                {
+                    continue;
+                }
+                trace!(
+                    "Thermo removing {symbol:?} from {cell:?}, outside {}..={}",
+                    lower[i],
+                    upper[i]
+                );
+                did_update |= sudoku.remove_candidate(cell, symbol)?;
+            }
+        }
+        Ok(did_update)
+    }
+
+    /// Solves a killer cage together with the thermometers running through it.
+    ///
+    /// A set of digits for the cage only survives if it adds up, has no repeats (only between
+    /// cells sharing a house, for a non-unique cage), and keeps the cells the cage shares with
+    /// a thermometer in thermometer order.
+    fn prune_cage(&self, sudoku: &mut Sudoku, cage: &Cage) -> Result<DidUpdateGrid, SudokuError> {
+        let orderings: Vec<(usize, usize)> = self
+            .thermos
+            .iter()
+            .flat_map(|thermo| {
+                let positions: Vec<usize> = thermo
+                    .iter()
+                    .filter_map(|c| cage.cells.iter().position(|x| x == c))
+                    .collect();
+                positions
+                    .windows(2)
+                    .map(|w| (w[0], w[1]))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if orderings.is_empty() {
+            return Ok(false);
+        }
+        let options = cage
+            .cells
+            .iter()
+            .map(|c| Ok(get_values(sudoku, &sudoku.get_cell(c)?.0)))
+            .collect::<Result<Vec<_>, SudokuError>>()?;
+        let houses = sudoku.get_houses();
+        let must_differ: Vec<Vec<bool>> = cage
+            .cells
+            .iter()
+            .map(|a| {
+                cage.cells
+                    .iter()
+                    .map(|b| cage.is_unique() || share_house(&houses, a, b))
+                    .collect()
+            })
+            .collect();
+        let mut keep: Vec<HashSet<Symbol>> = vec![HashSet::new(); cage.cells.len()];
+        let mut chosen = vec![];
+        self.search_cage(
+            cage.get_sum(),
+            &options,
+            &orderings,
+            &must_differ,
+            &mut chosen,
+            &mut keep,
+        );
+        let mut did_update = false;
+        for (i, cell) in cage.cells.iter().enumerate() {
+            for (symbol, _) in options[i].iter().filter(|(s, _)| !keep[i].contains(s)) {
+                trace!("Thermo removing {symbol:?} from {cell:?}, no cage combination fits");
+                did_update |= sudoku.remove_candidate(cell, symbol)?;
+            }
+        }
+        Ok(did_update)
+    }
+
+    fn search_cage(
+        &self,
+        sum: Option<u32>,
+        options: &[Vec<(Symbol, u32)>],
+        orderings: &[(usize, usize)],
+        must_differ: &[Vec<bool>],
+        chosen: &mut Vec<(Symbol, u32)>,
+        keep: &mut [HashSet<Symbol>],
+    ) {
+        let total: u32 = chosen.iter().map(|(_, v)| v).sum();
+        if sum.is_some_and(|s| total > s) {
+            return;
+        }
+        let i = chosen.len();
+        if i == options.len() {
+            if sum.is_none_or(|s| total == s) {
+                for (k, (symbol, _)) in chosen.iter().enumerate() {
+                    keep[k].insert(*symbol);
+                }
+            }
+            return;
+        }
+        for &(symbol, value) in &options[i] {
+            let repeated = chosen
+                .iter()
+                .enumerate()
+                .any(|(j, (s, _))| *s == symbol && must_differ[i][j]);
+            let out_of_order = orderings
+                .iter()
+                .filter(|(_, to)| *to == i)
+                .any(|(from, _)| from < &i && !self.in_order(chosen[*from].1, value))
+                || orderings
+                    .iter()
+                    .filter(|(from, _)| *from == i)
+                    .any(|(_, to)| to < &i && !self.in_order(value, chosen[*to].1));
+            if repeated || out_of_order {
+                continue;
+            }
+            chosen.push((symbol, value));
+            self.search_cage(sum, options, orderings, must_differ, chosen, keep);
+            chosen.pop();
+        }
+    }
+}
+
+impl Constraint for Thermo {
+    fn notify_update(
+        &self,
+        sudoku: &mut Sudoku,
+        cell: &Cell,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let mut did_update = false;
+        for thermo in self.thermos.iter().filter(|t| t.contains(cell)) {
+            did_update |= self.tighten_bounds(sudoku, thermo)?;
+        }
+        Ok(did_update)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_weak_links(&self, sudoku: &Sudoku) -> Vec<WeakLink> {
+        let mut links = vec![];
+        for pair in self.thermos.iter().flat_map(|thermo| thermo.windows(2)) {
+            let (from, to) = (pair[0], pair[1]);
+            let (from_values, to_values) = match (sudoku.get_cell(&from), sudoku.get_cell(&to)) {
+                (Ok(f), Ok(t)) => (get_values(sudoku, &f.0), get_values(sudoku, &t.0)),
+                _ => continue,
+            };
+            for ((a, a_value), (b, b_value)) in from_values.iter().cartesian_product(&to_values) {
+                if !self.in_order(*a_value, *b_value) {
+                    links.push(((from, *a), (to, *b)));
+                }
+            }
+        }
+        links
+    }
+
+    fn allows_swap(&self, _sudoku: &Sudoku, rectangle: &[Cell; 4]) -> bool {
+        !self.thermos.iter().flatten().any(|c| rectangle.contains(c))
+    }
+
+    fn is_violated(&self, sudoku: &Sudoku) -> bool {
+        self.thermos.iter().any(|thermo| {
+            thermo.windows(2).any(|pair| {
+                let solved = |c: &Cell| {
+                    sudoku
+                        .get_cell(c)
+                        .ok()
+                        .and_then(|d| d.try_get_solved())
+                        .and_then(|s| sudoku.get_value(s))
+                };
+                match (solved(&pair[0]), solved(&pair[1])) {
+                    (Some(a), Some(b)) => !self.in_order(a, b),
+                    _ => false,
+                }
+            })
+        })
+    }
+}
+
+/// Tightens the bounds along each thermometer.
+pub(crate) struct Thermometer;
+
+impl Strategy for Thermometer {
+    fn name(&self) -> &str {
+        "Thermometer"
+    }
+
+    fn weight(&self) -> f32 {
+        THERMO_WEIGHT
+    }
+
+    fn is_applicable(&self, sudoku: &Sudoku) -> bool {
+        sudoku.constraints.iter().any(|c| c.as_any().is::<Thermo>())
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        debug!("Running Thermometer");
+        for thermo in get_thermos(sudoku) {
+            for line in &thermo.thermos {
+                if thermo.tighten_bounds(sudoku, line)? {
+                    sudoku.note_regions(&[line]);
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Solves each killer cage together with the thermometers running through it.
+pub(crate) struct ThermometerInCage;
+
+impl Strategy for ThermometerInCage {
+    fn name(&self) -> &str {
+        "Thermometer in Cage"
+    }
+
+    fn weight(&self) -> f32 {
+        THERMO_CAGE_WEIGHT
+    }
+
+    fn is_applicable(&self, sudoku: &Sudoku) -> bool {
+        Thermometer.is_applicable(sudoku)
+            && sudoku.constraints.iter().any(|c| c.as_any().is::<Killer>())
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        debug!("Running Thermometer in Cage");
+        let cages = get_all_cages(sudoku);
+        for thermo in get_thermos(sudoku) {
+            for cage in &cages {
+                if thermo.prune_cage(sudoku, cage)? {
+                    sudoku.note_regions(&[&cage.cells]);
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn get_thermos(sudoku: &Sudoku) -> Vec<Thermo> {
+    sudoku
+        .constraints
+        .iter()
+        .filter_map(|c| c.as_any().downcast_ref::<Thermo>())
+        .cloned()
+        .collect()
+}
+
+/// The candidates of a cell with their values, smallest first.
+/// Symbols without a value cannot be placed on a thermometer, so they are left out.
+fn get_values(sudoku: &Sudoku, candidates: &Candidates) -> Vec<(Symbol, u32)> {
+    let mut values: Vec<(Symbol, u32)> = candidates
+        .iter()
+        .filter_map(|s| sudoku.get_value(s).map(|v| (*s, v)))
+        .collect();
+    values.sort_by_key(|(_, v)| *v);
+    values
+}
+
+fn first_at_least(values: &[(Symbol, u32)], bound: u32) -> u32 {
+    values
+        .iter()
+        .map(|(_, v)| *v)
+        .find(|v| *v >= bound)
+        .unwrap_or(u32::MAX)
+}
+
+fn last_at_most(values: &[(Symbol, u32)], bound: u32) -> u32 {
+    values
+        .iter()
+        .rev()
+        .map(|(_, v)| *v)
+        .find(|v| *v <= bound)
+        .unwrap_or(0)
+}
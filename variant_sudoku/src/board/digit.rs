@@ -1,6 +1,209 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
 
-pub type Candidates = Vec<Symbol>;
+/// The most symbols a sudoku can have, one for each bit of `Candidates`.
+pub(crate) const MAX_SYMBOLS: usize = u64::BITS as usize;
+
+/// The symbols a cell could still be, stored as a bit for each symbol of the sudoku.
+///
+/// The bits index into a sorted table of the sudoku's symbols, which every cell shares,
+/// so candidates are cheap to copy, compare and combine.
+#[derive(Clone)]
+pub struct Candidates {
+    mask: u64,
+    symbols: Rc<[Symbol]>,
+}
+
+impl Candidates {
+    /// Every one of `symbols`, which must be sorted and no more than `MAX_SYMBOLS` long.
+    pub(crate) fn all(symbols: Rc<[Symbol]>) -> Self {
+        debug_assert!(symbols.len() <= MAX_SYMBOLS);
+        debug_assert!(symbols.windows(2).all(|w| w[0].0 < w[1].0));
+        let mask = match symbols.len() {
+            MAX_SYMBOLS => u64::MAX,
+            len => (1 << len) - 1,
+        };
+        Self { mask, symbols }
+    }
+
+    /// Candidates of the same sudoku holding just `symbols`, leaving out any it does not have.
+    pub fn with_symbols<'a>(&self, symbols: impl IntoIterator<Item = &'a Symbol>) -> Self {
+        let mask = symbols
+            .into_iter()
+            .filter_map(|s| self.bit(s))
+            .fold(0, |mask, bit| mask | bit);
+        Self {
+            mask,
+            symbols: self.symbols.clone(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    pub fn contains(&self, symbol: &Symbol) -> bool {
+        self.bit(symbol).is_some_and(|bit| self.mask & bit != 0)
+    }
+
+    pub fn first(&self) -> Option<&Symbol> {
+        self.iter().next()
+    }
+
+    /// The symbols, in sorted order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            mask: self.mask,
+            symbols: &self.symbols,
+        }
+    }
+
+    /// Removes `symbol`, returning whether it was a candidate.
+    pub(crate) fn remove(&mut self, symbol: &Symbol) -> bool {
+        let had = self.contains(symbol);
+        if let Some(bit) = self.bit(symbol) {
+            self.mask &= !bit;
+        }
+        had
+    }
+
+    /// Keeps only the symbols also in `other`, returning whether any were removed.
+    pub(crate) fn keep(&mut self, other: &Self) -> bool {
+        self.check_same_symbols(other);
+        let before = self.mask;
+        self.mask &= other.mask;
+        self.mask != before
+    }
+
+    /// The symbols in either.
+    pub(crate) fn union(&self, other: &Self) -> Self {
+        self.check_same_symbols(other);
+        Self {
+            mask: self.mask | other.mask,
+            symbols: self.symbols.clone(),
+        }
+    }
+
+    /// Whether any symbol is in both.
+    pub(crate) fn intersects(&self, other: &Self) -> bool {
+        self.check_same_symbols(other);
+        self.mask & other.mask != 0
+    }
+
+    fn bit(&self, symbol: &Symbol) -> Option<u64> {
+        self.symbols
+            .binary_search_by_key(&symbol.0, |s| s.0)
+            .ok()
+            .map(|i| 1 << i)
+    }
+
+    /// Combining masks only makes sense when the bits stand for the same symbols.
+    fn check_same_symbols(&self, other: &Self) {
+        debug_assert!(self.symbols == other.symbols);
+    }
+}
+
+impl PartialEq for Candidates {
+    fn eq(&self, other: &Self) -> bool {
+        self.mask == other.mask && self.symbols == other.symbols
+    }
+}
+
+impl Eq for Candidates {}
+
+impl Hash for Candidates {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mask.hash(state);
+    }
+}
+
+impl Debug for Candidates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Iterates over the symbols of `Candidates` by reference.
+#[derive(Clone)]
+pub struct Iter<'a> {
+    mask: u64,
+    symbols: &'a [Symbol],
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Symbol;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.mask == 0 {
+            return None;
+        }
+        let i = self.mask.trailing_zeros() as usize;
+        self.mask &= self.mask - 1;
+        Some(&self.symbols[i])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.mask.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a> IntoIterator for &'a Candidates {
+    type Item = &'a Symbol;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterates over the symbols of `Candidates` by value.
+#[derive(Clone)]
+pub struct IntoIter {
+    mask: u64,
+    symbols: Rc<[Symbol]>,
+}
+
+impl Iterator for IntoIter {
+    type Item = Symbol;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.mask == 0 {
+            return None;
+        }
+        let i = self.mask.trailing_zeros() as usize;
+        self.mask &= self.mask - 1;
+        Some(self.symbols[i])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.mask.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for Candidates {
+    type Item = Symbol;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            mask: self.mask,
+            symbols: self.symbols,
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Digit(pub Candidates);
@@ -9,10 +212,6 @@ pub struct Digit(pub Candidates);
 pub struct Symbol(pub char);
 
 impl Symbol {
-    pub fn get_number(&self) -> Option<u32> {
-        self.0.to_digit(10)
-    }
-
     #[allow(dead_code)]
     pub fn from_num(num: u8) -> Self {
         let c = num as char;
@@ -48,6 +247,11 @@ impl Digit {
         Some(&self.0)
     }
 
+    /// Replaces the candidates with `symbols`, dropping any that are not symbols of the sudoku.
+    pub fn set_candidates(&mut self, symbols: &[Symbol]) {
+        self.0 = self.0.with_symbols(symbols);
+    }
+
     pub(crate) fn get_char(&self) -> char {
         match self.try_get_solved() {
             Some(s) => s.0,
@@ -75,15 +279,11 @@ impl Debug for Symbol {
 }
 
 #[allow(dead_code)]
-pub(crate) fn intersect_candidates(c: Vec<&Candidates>) -> Candidates {
-    if c.is_empty() {
-        return Vec::new();
+pub(crate) fn intersect_candidates(c: Vec<&Candidates>) -> Option<Candidates> {
+    let (first, rest) = c.split_first()?;
+    let mut intersection = (*first).clone();
+    for cand in rest {
+        intersection.keep(cand);
     }
-
-    let first = c[0];
-    first
-        .iter()
-        .cloned()
-        .filter(|sym| c.iter().all(|cand| cand.contains(sym)))
-        .collect()
+    Some(intersection)
 }
@@ -1,13 +1,18 @@
+pub(super) mod arrow;
 pub(super) mod killer;
 pub(super) mod yaml;
 
 use crate::{
     board::{
-        constraints::RcConstraint,
+        constraints::{kropki::KropkiColor, RcConstraint},
         digit::Symbol,
         parser::{
+            arrow::YamlArrow,
             killer::YamlKillerCage,
-            yaml::{new_standard_constraints, YamlConstraint, YamlSudoku},
+            yaml::{
+                new_diagonal_constraints, new_jigsaw_constraints, new_kropki_constraints,
+                new_standard_constraints, new_thermo_constraints, YamlConstraint, YamlSudoku,
+            },
         },
         sudoku::Cell,
     },
@@ -20,16 +25,26 @@ impl<'de> serde::de::Deserialize<'de> for Sudoku {
         D: serde::Deserializer<'de>,
     {
         let helper = YamlSudoku::deserialize(deserializer)?;
-        let givens = helper.generate_given_board();
-        let constraints =
-            parse_constraints(helper.constraints).map_err(serde::de::Error::custom)?;
+        let givens = helper
+            .generate_given_board()
+            .map_err(serde::de::Error::custom)?;
+        let constraints = parse_constraints(helper.constraints, givens.size())
+            .map_err(serde::de::Error::custom)?;
+        let value_overrides = helper
+            .symbol_values
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(c, v)| (Symbol(c), v))
+            .collect();
         let sudoku = match helper.valid_digits {
             Some(v) => {
                 let valid_symbols = v.trim().chars().map(Symbol).collect();
-                Sudoku::new_with_valid_digits(givens, constraints, valid_symbols)
+                Sudoku::new_with_valid_digits(givens, constraints, valid_symbols, value_overrides)
             }
-            None => Sudoku::new(givens, constraints),
+            None => Sudoku::new(givens, constraints, value_overrides),
         };
+        let mut sudoku = sudoku.map_err(serde::de::Error::custom)?;
+        sudoku.set_assume_unique(helper.assume_unique.unwrap_or(false));
         Ok(sudoku)
     }
 }
@@ -46,22 +61,44 @@ impl<'de> serde::de::Deserialize<'de> for Cell {
 
 fn parse_constraints(
     yaml_constraints: Option<Vec<YamlConstraint>>,
+    size: (usize, usize),
 ) -> Result<Vec<RcConstraint>, SudokuError> {
+    let yaml_constraints =
+        yaml_constraints.unwrap_or(vec![YamlConstraint::Standard { box_size: None }]);
+    let has_jigsaw = yaml_constraints
+        .iter()
+        .any(|c| matches!(c, YamlConstraint::Jigsaw { .. }));
     let nested_constraints = yaml_constraints
-        .unwrap_or(vec![YamlConstraint::Standard])
         .into_iter()
-        .map(yaml_to_constraint)
+        .map(|c| yaml_to_constraint(c, size, has_jigsaw))
         .collect::<Result<Vec<_>, _>>()?;
 
     let flat_constraints = nested_constraints.into_iter().flatten().collect::<Vec<_>>();
     Ok(flat_constraints)
 }
 
-fn yaml_to_constraint(constraint: YamlConstraint) -> Result<Vec<RcConstraint>, SudokuError> {
+fn yaml_to_constraint(
+    constraint: YamlConstraint,
+    size: (usize, usize),
+    has_jigsaw: bool,
+) -> Result<Vec<RcConstraint>, SudokuError> {
     let ok = match constraint {
-        YamlConstraint::Standard => new_standard_constraints(),
+        YamlConstraint::Standard { box_size } => {
+            new_standard_constraints(box_size, size, has_jigsaw)?
+        }
+        YamlConstraint::Jigsaw { regions } => new_jigsaw_constraints(&regions, size)?,
         YamlConstraint::Killer { cages } => YamlKillerCage::to_real(cages)?,
-        e => return Err(SudokuError::UnsupportedConstraint(format!("{e:?}"))),
+        YamlConstraint::Diagonal { variants } => new_diagonal_constraints(&variants, size)?,
+        YamlConstraint::Arrow { arrows } => YamlArrow::to_real(arrows, size)?,
+        YamlConstraint::Thermo { variant, thermos } => {
+            new_thermo_constraints(variant, thermos, size)?
+        }
+        YamlConstraint::BlackKropki { variant, cells } => {
+            new_kropki_constraints(KropkiColor::Black, variant, cells, size)?
+        }
+        YamlConstraint::WhiteKropki { variant, cells } => {
+            new_kropki_constraints(KropkiColor::White, variant, cells, size)?
+        }
     };
     Ok(ok)
 }
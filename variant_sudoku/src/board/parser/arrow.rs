@@ -0,0 +1,46 @@
+use std::rc::Rc;
+
+use log::debug;
+use serde::Deserialize;
+
+use crate::{
+    board::{
+        constraints::{
+            arrow::{Arrow, ArrowLine},
+            standard::House,
+            RcConstraint,
+        },
+        sudoku::Cell,
+    },
+    errors::SudokuError,
+};
+
+#[derive(Debug, Deserialize)]
+pub(super) struct YamlArrow {
+    circle: House,
+    line: House,
+}
+
+impl YamlArrow {
+    pub(super) fn to_real(
+        arrows: Vec<YamlArrow>,
+        size: (usize, usize),
+    ) -> Result<Vec<RcConstraint>, SudokuError> {
+        debug!("Converting YamlArrow to real arrows: {arrows:?}");
+        let (rows, cols) = size;
+        let mut real_arrows = vec![];
+        for arrow in arrows {
+            if arrow.circle.is_empty() || arrow.line.is_empty() {
+                return Err(SudokuError::ConstraintPredicateInvalid(
+                    "Arrow must have a circle and a line".to_string(),
+                ));
+            }
+            let cells: Vec<&Cell> = arrow.circle.iter().chain(arrow.line.iter()).collect();
+            if let Some(cell) = cells.iter().find(|c| c.row >= rows || c.col >= cols) {
+                return Err(SudokuError::OutOfBoundsAccess(**cell));
+            }
+            real_arrows.push(ArrowLine::new(arrow.circle, arrow.line));
+        }
+        Ok(vec![Rc::new(Arrow::new(real_arrows))])
+    }
+}
@@ -19,12 +19,20 @@ pub(super) struct YamlKillerCage {
     cells: Vec<Cell>,
     value: Option<u32>,
     operation: Option<YamlKillerOperation>,
+    /// Cages are unique by default, set to `false` to allow repeated digits.
+    unique: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
 enum YamlKillerOperation {
     #[serde(rename = "sum")]
     Sum,
+    #[serde(rename = "product")]
+    Product,
+    #[serde(rename = "difference")]
+    Difference,
+    #[serde(rename = "quotient")]
+    Quotient,
 }
 
 fn killer_value_err() -> SudokuError {
@@ -47,6 +55,15 @@ impl YamlKillerCage {
                 Some(YamlKillerOperation::Sum) => {
                     KillerMarking::Sum(cage.value.ok_or_else(killer_value_err)?)
                 }
+                Some(YamlKillerOperation::Product) => {
+                    KillerMarking::Product(cage.value.ok_or_else(killer_value_err)?)
+                }
+                Some(YamlKillerOperation::Difference) => {
+                    KillerMarking::Difference(cage.value.ok_or_else(killer_value_err)?)
+                }
+                Some(YamlKillerOperation::Quotient) => {
+                    KillerMarking::Quotient(cage.value.ok_or_else(killer_value_err)?)
+                }
                 None => {
                     // If there is a value, assume it is a sum
                     if let Some(value) = cage.value {
@@ -56,7 +73,7 @@ impl YamlKillerCage {
                     }
                 }
             };
-            killer_cages.push(Cage::new(cage.cells, marking));
+            killer_cages.push(Cage::new(cage.cells, marking, cage.unique.unwrap_or(true)));
         }
         Ok(vec![Rc::new(Killer::new(killer_cages))])
     }
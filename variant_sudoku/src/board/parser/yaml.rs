@@ -1,19 +1,35 @@
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 
 use grid::Grid;
 use serde::Deserialize;
 
-use crate::board::{
-    constraints::{standard::HouseUnique, RcConstraint},
-    digit::Symbol,
-    parser::killer::YamlKillerCage,
-    sudoku::Cell,
+use crate::{
+    board::{
+        constraints::{
+            diagonal::Diagonal,
+            jigsaw::get_jigsaw_houses,
+            kropki::{Kropki, KropkiColor},
+            standard::{get_box_size, validate_box_size, BoxSize, House, HouseUnique},
+            thermo::Thermo,
+            RcConstraint,
+        },
+        digit::Symbol,
+        parser::{arrow::YamlArrow, killer::YamlKillerCage},
+        sudoku::Cell,
+    },
+    errors::SudokuError,
 };
 
 #[derive(Deserialize)]
 pub(super) struct YamlSudoku {
     pub(super) board: String,
     pub(super) valid_digits: Option<String>,
+    /// Overrides the number a symbol stands for in arithmetic constraints.
+    ///
+    /// Otherwise symbols are read as base 36 digits, so `0` is worth 0 and `A` is worth 10.
+    pub(super) symbol_values: Option<HashMap<char, u32>>,
+    /// Enables strategies which rely on the puzzle having a single solution.
+    pub(super) assume_unique: Option<bool>,
     pub(super) constraints: Option<Vec<YamlConstraint>>,
 }
 
@@ -22,9 +38,23 @@ pub(super) struct YamlSudoku {
 #[allow(dead_code)]
 pub(super) enum YamlConstraint {
     #[serde(rename = "standard")]
-    Standard,
+    Standard {
+        /// Overrides the (rows, cols) of each box, which is otherwise derived from the grid size.
+        #[serde(default)]
+        box_size: Option<BoxSize>,
+    },
     #[serde(rename = "killer")]
     Killer { cages: Vec<YamlKillerCage> },
+    #[serde(rename = "jigsaw")]
+    Jigsaw { regions: String },
+    #[serde(rename = "arrow")]
+    Arrow { arrows: Vec<YamlArrow> },
+    #[serde(rename = "thermo")]
+    Thermo {
+        #[serde(default)]
+        variant: Option<String>,
+        thermos: Vec<House>,
+    },
     #[serde(rename = "diagonal")]
     Diagonal { variants: Vec<String> },
     #[serde(rename = "black_kropki")]
@@ -42,29 +72,160 @@ pub(super) enum YamlConstraint {
 }
 
 impl YamlSudoku {
-    pub(super) fn generate_given_board(&self) -> Grid<Option<Symbol>> {
-        let cells: Vec<Option<Symbol>> = self
+    /// Reads the givens from the board, one line per row.
+    ///
+    /// The grid does not have to be square, but every line must have the same number of cells.
+    pub(super) fn generate_given_board(&self) -> Result<Grid<Option<Symbol>>, SudokuError> {
+        let rows: Vec<Vec<Option<Symbol>>> = self
             .board
             .lines()
-            .flat_map(|row| {
-                row.chars().map(|d| {
-                    if d.is_ascii_alphanumeric() {
-                        Some(Symbol(d))
-                    } else {
-                        None
-                    }
-                })
+            .map(|row| {
+                row.chars()
+                    .map(|d| {
+                        if d.is_ascii_alphanumeric() {
+                            Some(Symbol(d))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
             })
-            .collect::<Vec<Option<Symbol>>>();
-        let cols = cells.len().isqrt();
-        Grid::from_vec(cells, cols)
+            .collect();
+        let cols = match rows.first() {
+            Some(r) if !r.is_empty() => r.len(),
+            _ => return Err(SudokuError::InvalidBoard("Board is empty".to_string())),
+        };
+        let lines = self.board.lines().zip(rows.iter()).enumerate();
+        for (i, (line, row)) in lines {
+            if row.len() != cols {
+                return Err(SudokuError::InvalidBoard(format!(
+                    "Line {} {line:?} has {} cells, expected {cols} like the first line",
+                    i + 1,
+                    row.len()
+                )));
+            }
+        }
+        Ok(Grid::from_vec(rows.into_iter().flatten().collect(), cols))
     }
 }
 
-pub(super) fn new_standard_constraints() -> Vec<RcConstraint> {
-    vec![
+pub(super) fn new_standard_constraints(
+    box_size: Option<BoxSize>,
+    size: (usize, usize),
+    has_jigsaw: bool,
+) -> Result<Vec<RcConstraint>, SudokuError> {
+    if has_jigsaw {
+        // The jigsaw regions take the place of the boxes.
+        if box_size.is_some() {
+            return Err(SudokuError::ConstraintPredicateInvalid(
+                "A box size cannot be given alongside a jigsaw".to_string(),
+            ));
+        }
+        return Ok(vec![Rc::new(HouseUnique::Row), Rc::new(HouseUnique::Col)]);
+    }
+    let box_size = match box_size {
+        Some(b) => validate_box_size(size, b)?,
+        None => get_box_size(size)?,
+    };
+    Ok(vec![
         Rc::new(HouseUnique::Row),
         Rc::new(HouseUnique::Col),
-        Rc::new(HouseUnique::Box),
-    ]
+        Rc::new(HouseUnique::Box(box_size)),
+    ])
+}
+
+pub(super) fn new_jigsaw_constraints(
+    regions: &str,
+    size: (usize, usize),
+) -> Result<Vec<RcConstraint>, SudokuError> {
+    let houses = get_jigsaw_houses(regions, size)?;
+    Ok(vec![Rc::new(HouseUnique::Custom(houses))])
+}
+
+pub(super) fn new_diagonal_constraints(
+    variants: &[String],
+    size: (usize, usize),
+) -> Result<Vec<RcConstraint>, SudokuError> {
+    if variants.is_empty() {
+        return Err(SudokuError::ConstraintPredicateInvalid(
+            "Diagonal constraint must have at least one variant".to_string(),
+        ));
+    }
+    let houses = variants
+        .iter()
+        .map(|v| Diagonal::from_variant(v)?.get_house(size))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(vec![Rc::new(HouseUnique::Custom(houses))])
+}
+
+pub(super) fn new_kropki_constraints(
+    color: KropkiColor,
+    variant: Option<String>,
+    cells: Vec<[Cell; 2]>,
+    size: (usize, usize),
+) -> Result<Vec<RcConstraint>, SudokuError> {
+    let negative = match variant.as_deref() {
+        None => false,
+        Some("full") => true,
+        Some(v) => {
+            return Err(SudokuError::ConstraintPredicateInvalid(format!(
+                "Unknown kropki variant {v:?}, expected full"
+            )))
+        }
+    };
+    let (rows, cols) = size;
+    if let Some(cell) = cells
+        .iter()
+        .flatten()
+        .find(|c| c.row >= rows || c.col >= cols)
+    {
+        return Err(SudokuError::ConstraintPredicateInvalid(format!(
+            "Kropki dot cell {cell:?} is outside the grid"
+        )));
+    }
+    if let Some([a, b]) = cells
+        .iter()
+        .find(|[a, b]| a.row.abs_diff(b.row) + a.col.abs_diff(b.col) != 1)
+    {
+        return Err(SudokuError::ConstraintPredicateInvalid(format!(
+            "Kropki dot between {a:?} and {b:?} must join orthogonally adjacent cells"
+        )));
+    }
+    Ok(vec![Rc::new(Kropki::new(color, cells, negative))])
+}
+
+pub(super) fn new_thermo_constraints(
+    variant: Option<String>,
+    thermos: Vec<House>,
+    size: (usize, usize),
+) -> Result<Vec<RcConstraint>, SudokuError> {
+    let slow = match variant.as_deref() {
+        None => false,
+        Some("slow") => true,
+        Some(v) => {
+            return Err(SudokuError::ConstraintPredicateInvalid(format!(
+                "Unknown thermo variant {v:?}, expected slow"
+            )))
+        }
+    };
+    let (rows, cols) = size;
+    for thermo in &thermos {
+        if thermo.len() < 2 {
+            return Err(SudokuError::ConstraintPredicateInvalid(
+                "Thermo must have at least two cells".to_string(),
+            ));
+        }
+        if let Some(cell) = thermo.iter().find(|c| c.row >= rows || c.col >= cols) {
+            return Err(SudokuError::OutOfBoundsAccess(*cell));
+        }
+        if let Some(pair) = thermo.windows(2).find(|p| {
+            p[0] == p[1] || p[0].row.abs_diff(p[1].row) > 1 || p[0].col.abs_diff(p[1].col) > 1
+        }) {
+            return Err(SudokuError::ConstraintPredicateInvalid(format!(
+                "Thermo cells {:?} and {:?} must be adjacent",
+                pair[0], pair[1]
+            )));
+        }
+    }
+    Ok(vec![Rc::new(Thermo::new(thermos, slow))])
 }
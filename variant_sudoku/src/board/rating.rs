@@ -0,0 +1,49 @@
+use std::fmt::{self, Display};
+
+use crate::board::{solve_log::SolveLog, solver::brute_force::BRUTE_FORCE_WEIGHT};
+
+/// How hard a sudoku is, from solving it with the easiest technique available at each step.
+///
+/// As with Sudoku Explainer ratings, the difficulty is the weight of the hardest step needed,
+/// with the total weight of every step telling apart long solves from short ones.
+///
+/// The scale differs from Sudoku Explainer's for singles. Naked singles are filled in as
+/// candidates are removed rather than taken as steps, so they are never counted: a sudoku
+/// solved by them alone rates 0 where Sudoku Explainer gives 2.3, and totals leave them out.
+/// Only a `max` above 2.3 can be compared between the two.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    /// The weight of the hardest step.
+    pub max: f32,
+    /// The weights of all the steps added up.
+    pub total: f32,
+    /// The steps the rating was worked out from.
+    pub log: SolveLog,
+}
+
+impl Rating {
+    pub(crate) fn new(log: SolveLog) -> Self {
+        Self {
+            max: log.steps.iter().map(|s| s.weight).fold(0.0, f32::max),
+            total: log.steps.iter().map(|s| s.weight).fold(0.0, |a, b| a + b),
+            log,
+        }
+    }
+
+    /// Whether the sudoku was solved without having to guess.
+    pub fn is_logical(&self) -> bool {
+        self.max < BRUTE_FORCE_WEIGHT
+    }
+}
+
+impl Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} (total {:.1} over {} steps)",
+            self.max,
+            self.total,
+            self.log.steps.len()
+        )
+    }
+}
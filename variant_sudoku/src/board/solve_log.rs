@@ -0,0 +1,84 @@
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+
+use crate::board::{digit::Symbol, sudoku::Cell};
+
+/// One deduction made while solving, along with what it changed on the board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveStep {
+    /// The strategy, or constraint, which made the deduction.
+    pub technique: String,
+    /// How hard the technique is, from the weight it runs at.
+    pub weight: f32,
+    /// The houses, cages or other groups of cells the deduction was drawn from.
+    pub regions: Vec<Vec<Cell>>,
+    /// The cells solved by the step, including those left with a single candidate by it.
+    pub placements: Vec<(Cell, Symbol)>,
+    /// The candidates removed by the step, leaving out those only removed because a cell seeing
+    /// them was solved, which placing the digits again takes care of.
+    pub eliminations: Vec<(Cell, Symbol)>,
+}
+
+/// Every step taken while solving a sudoku, in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveLog {
+    pub steps: Vec<SolveStep>,
+    /// The regions noted by the strategy running now, for the step it makes.
+    pub(crate) pending_regions: Vec<Vec<Cell>>,
+}
+
+impl SolveStep {
+    /// Every cell of the regions the step was drawn from, for pointing a player at them.
+    pub fn get_focus_cells(&self) -> Vec<Cell> {
+        self.regions.iter().flatten().copied().unique().collect()
+    }
+}
+
+impl Display for SolveStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:.1})", self.technique, self.weight)?;
+        if !self.regions.is_empty() {
+            let regions = self.regions.iter().map(|r| describe_region(r)).join(", ");
+            write!(f, " using {regions}")?;
+        }
+        let placements = self
+            .placements
+            .iter()
+            .map(|(cell, symbol)| format!("places {symbol} in {cell}"));
+        let eliminations = self
+            .eliminations
+            .iter()
+            .into_group_map_by(|(_, symbol)| *symbol)
+            .into_iter()
+            .sorted_by_key(|(symbol, _)| symbol.0)
+            .map(|(symbol, cells)| {
+                let cells = cells.iter().map(|(cell, _)| cell).join(", ");
+                format!("removes {symbol} from {cells}")
+            });
+        write!(f, ": {}", placements.chain(eliminations).join("; "))
+    }
+}
+
+impl Display for SolveLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{}. {step}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the cells of a region, in the compact `r3c125` or `r123c456` form when they fill
+/// every row and column they are in, and the numbers are all single digits.
+fn describe_region(region: &[Cell]) -> String {
+    let rows: Vec<usize> = region.iter().map(|c| c.row).sorted().dedup().collect();
+    let cols: Vec<usize> = region.iter().map(|c| c.col).sorted().dedup().collect();
+    let single_digits = rows.iter().chain(&cols).all(|i| *i < 9);
+    if single_digits && rows.len() * cols.len() == region.iter().unique().count() {
+        let join = |indices: &[usize]| indices.iter().map(|i| i + 1).join("");
+        format!("r{}c{}", join(&rows), join(&cols))
+    } else {
+        region.iter().join(" ")
+    }
+}
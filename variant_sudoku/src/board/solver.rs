@@ -1,2 +1,7 @@
-mod brute_force;
+pub(crate) mod als;
+pub(crate) mod brute_force;
+pub(crate) mod chains;
 pub(crate) mod house;
+pub(crate) mod killer;
+pub(crate) mod uniqueness;
+pub(crate) mod wing;
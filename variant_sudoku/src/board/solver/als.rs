@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use log::debug;
+
+use crate::{
+    board::{
+        constraints::standard::HouseSet,
+        digit::{Candidates, Symbol},
+        solver::wing::remove_from_common_peers,
+        sudoku::{Cell, DidUpdateGrid, Peers},
+    },
+    errors::SudokuError,
+    Sudoku,
+};
+
+/// The most cells an almost locked set may have.
+const MAX_ALS_SIZE: usize = 4;
+
+/// ALS-XZ takes two almost locked sets with a restricted common candidate X, one whose cells
+/// in either set all see each other. At most one of the sets holds X, so the other is locked,
+/// and any other candidate Z they share has to be in one of them.
+/// Z is removed from every cell seeing all the cells of both sets that could be Z.
+pub(crate) fn als_xz(sudoku: &mut Sudoku, houses: &HouseSet) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running ALS-XZ, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let peers = sudoku.get_peers();
+    let sets = get_almost_locked_sets(sudoku, houses);
+    let links = get_restricted_commons(&sets, &peers);
+    for (i, a) in sets.iter().enumerate() {
+        for &(j, x) in links[i].iter().filter(|(j, _)| *j > i) {
+            let b = &sets[j];
+            for (z, a_cells, b_cells) in a.shared(b).filter(|(z, _, _)| *z != x) {
+                if remove_from_common_peers(sudoku, &peers, &[a_cells, b_cells].concat(), &z)? {
+                    debug!(
+                        "Found ALS-XZ on {:?} and {:?} with X {x:?}, Z {z:?}",
+                        a.cells, b.cells
+                    );
+                    sudoku.note_regions(&[&a.cells, &b.cells]);
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// An ALS-XY-Wing has a pivot almost locked set C, with a restricted common candidate X to a set A,
+/// and a different one Y to a set B. If neither A nor B held a candidate Z they share, A would
+/// be locked and hold X, so C would be locked and hold Y, leaving B too few candidates.
+/// Z is removed from every cell seeing all the cells of both sets that could be Z.
+pub(crate) fn als_xy_wing(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running ALS-XY-Wing, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let peers = sudoku.get_peers();
+    let sets = get_almost_locked_sets(sudoku, houses);
+    let links = get_restricted_commons(&sets, &peers);
+    for (pivot, pivot_links) in sets.iter().zip(&links) {
+        for (&(a, x), &(b, y)) in pivot_links.iter().tuple_combinations() {
+            let (a, b) = (&sets[a], &sets[b]);
+            if x == y || a.overlaps(b) {
+                continue;
+            }
+            for (z, a_cells, b_cells) in a.shared(b).filter(|(z, _, _)| *z != x && *z != y) {
+                if remove_from_common_peers(sudoku, &peers, &[a_cells, b_cells].concat(), &z)? {
+                    debug!(
+                        "Found ALS-XY-Wing with pivot {:?} on {:?} and {:?}, Z {z:?}",
+                        pivot.cells, a.cells, b.cells
+                    );
+                    sudoku.note_regions(&[&pivot.cells, &a.cells, &b.cells]);
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// `n` unsolved cells within a house which have `n + 1` candidates between them.
+/// If any one of the candidates is taken away, the rest are locked into the cells.
+struct AlmostLockedSet {
+    cells: Vec<Cell>,
+    /// Every symbol the set could be, so sets are compared cheaply.
+    candidates: Candidates,
+    /// Each of the `candidates`, with the cells of the set that could be it.
+    cells_by_symbol: Vec<(Symbol, Vec<Cell>)>,
+}
+
+impl AlmostLockedSet {
+    fn new(cells: &[&(Cell, Candidates)]) -> Self {
+        let candidates = cells[1..]
+            .iter()
+            .fold(cells[0].1.clone(), |all, (_, c)| all.union(c));
+        let cells_by_symbol = candidates
+            .iter()
+            .map(|symbol| {
+                let with_symbol = cells
+                    .iter()
+                    .filter(|(_, candidates)| candidates.contains(symbol))
+                    .map(|(cell, _)| *cell)
+                    .collect();
+                (*symbol, with_symbol)
+            })
+            .collect();
+        Self {
+            cells: cells.iter().map(|(cell, _)| *cell).collect(),
+            candidates,
+            cells_by_symbol,
+        }
+    }
+
+    /// The cells of the set that could be `symbol`.
+    fn cells_with(&self, symbol: &Symbol) -> Option<&[Cell]> {
+        self.cells_by_symbol
+            .iter()
+            .find(|(s, _)| s == symbol)
+            .map(|(_, cells)| cells.as_slice())
+    }
+
+    /// The candidates of both sets, along with the cells of each set that could be them.
+    fn shared<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> impl Iterator<Item = (Symbol, &'a [Cell], &'a [Cell])> {
+        self.cells_by_symbol.iter().filter_map(move |(symbol, a)| {
+            other
+                .cells_with(symbol)
+                .map(|b| (*symbol, a.as_slice(), b))
+        })
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.cells.iter().any(|c| other.cells.contains(c))
+    }
+
+    /// The candidates shared with `other` where every cell that could be it in one set
+    /// sees every cell that could be it in the other, so only one of the sets can hold it.
+    fn restricted_commons(&self, other: &Self, peers: &Peers) -> Vec<Symbol> {
+        if !self.candidates.intersects(&other.candidates) || self.overlaps(other) {
+            return vec![];
+        }
+        self.shared(other)
+            .filter(|(_, a, b)| a.iter().all(|a| b.iter().all(|b| peers.sees(a, b))))
+            .map(|(symbol, _, _)| symbol)
+            .collect()
+    }
+}
+
+/// Every almost locked set of up to `MAX_ALS_SIZE` cells within a house.
+fn get_almost_locked_sets(sudoku: &Sudoku, houses: &HouseSet) -> Vec<AlmostLockedSet> {
+    let mut seen: HashSet<Vec<Cell>> = HashSet::new();
+    let mut sets = vec![];
+    for house in houses {
+        let unsolved: Vec<(Cell, Candidates)> = house
+            .iter()
+            .filter_map(|c| {
+                sudoku
+                    .get_cell(c)
+                    .ok()
+                    .and_then(|d| d.try_get_candidates())
+                    .map(|candidates| (*c, candidates.clone()))
+            })
+            .collect();
+        for size in 1..=MAX_ALS_SIZE.min(unsolved.len().saturating_sub(1)) {
+            let small = unsolved.iter().filter(|(_, s)| s.len() <= size + 1);
+            for combo in small.combinations(size) {
+                let count = combo.iter().flat_map(|(_, s)| s).unique().count();
+                if count != size + 1 {
+                    continue;
+                }
+                let mut key: Vec<Cell> = combo.iter().map(|(cell, _)| *cell).collect();
+                key.sort_by_key(|c| (c.row, c.col));
+                if seen.insert(key) {
+                    sets.push(AlmostLockedSet::new(&combo));
+                }
+            }
+        }
+    }
+    sets
+}
+
+/// The restricted common candidates between every pair of sets, listed against both of them
+/// by the index of the other set.
+fn get_restricted_commons(sets: &[AlmostLockedSet], peers: &Peers) -> Vec<Vec<(usize, Symbol)>> {
+    let mut links = vec![vec![]; sets.len()];
+    for ((i, a), (j, b)) in sets.iter().enumerate().tuple_combinations() {
+        for x in a.restricted_commons(b, peers) {
+            links[i].push((j, x));
+            links[j].push((i, x));
+        }
+    }
+    links
+}
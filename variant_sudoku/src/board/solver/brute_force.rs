@@ -1 +1,60 @@
+use log::debug;
 
+use crate::{errors::SudokuError, Sudoku};
+
+/// After a guess only the cheaper strategies are run, as it is quicker to make
+/// another guess than to look for a harder technique at every step of the search.
+const GUESS_MAX_STRATEGY_WEIGHT: f32 = 3.0;
+
+/// The weight logged for finishing a puzzle by guessing, above any logical strategy.
+pub(crate) const BRUTE_FORCE_WEIGHT: f32 = 10.0;
+
+/// Guess-and-propagate search, used once the logical strategies stall.
+///
+/// Picks the unsolved cell with the fewest candidates and tries each of them in turn on a
+/// clone of the sudoku, letting the usual `notify`/`use_strategies` loop propagate the guess.
+/// Returns every solved sudoku found, stopping as soon as `limit` solutions have been collected.
+pub(crate) fn brute_force(sudoku: &Sudoku, limit: usize) -> Result<Vec<Sudoku>, SudokuError> {
+    let mut solutions = vec![];
+    let mut start = sudoku.clone();
+    start.max_strategy_weight = sudoku.max_strategy_weight.min(GUESS_MAX_STRATEGY_WEIGHT);
+    search(&start, limit, &mut solutions)?;
+    for solution in &mut solutions {
+        solution.max_strategy_weight = sudoku.max_strategy_weight;
+    }
+    Ok(solutions)
+}
+
+fn search(sudoku: &Sudoku, limit: usize, solutions: &mut Vec<Sudoku>) -> Result<(), SudokuError> {
+    let (cell, candidates) = match sudoku
+        .indexed_candidates()
+        .into_iter()
+        .min_by_key(|(_, candidates)| candidates.len())
+    {
+        Some((cell, candidates)) => (cell, candidates.clone()),
+        None => return Ok(()),
+    };
+    for symbol in candidates {
+        if solutions.len() >= limit {
+            return Ok(());
+        }
+        debug!("Guessing {symbol:?} in {cell:?}");
+        let mut guess = sudoku.clone();
+        guess.place_digit(&cell, &symbol)?;
+        guess.solve_logically()?;
+        if guess.is_unsolveable() {
+            debug!("Guess {symbol:?} in {cell:?} is a contradiction");
+            continue;
+        }
+        if guess.is_solved() {
+            debug!(
+                "Guess {symbol:?} in {cell:?} found solution {}",
+                solutions.len() + 1
+            );
+            solutions.push(guess);
+            continue;
+        }
+        search(&guess, limit, solutions)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use std::collections::{HashMap, VecDeque};
+
+use itertools::Itertools;
+use log::{debug, trace};
+
+use crate::{
+    board::{
+        constraints::standard::{get_cells_in_house, is_complete_house, HouseSet},
+        digit::Symbol,
+        sudoku::{Cell, DidUpdateGrid},
+    },
+    errors::SudokuError,
+    Sudoku,
+};
+
+/// A symbol in a cell, which is either true (the cell holds that symbol) or false.
+type Node = (Cell, Symbol);
+
+/// A node of the graph, by its index, and whether it is assumed true.
+type State = (usize, bool);
+
+/// Simple Coloring looks at the conjugate pairs of a single symbol, the houses where it has
+/// only two places left. Exactly one cell of each pair holds the symbol, so the cells joined up
+/// by these pairs split into two colours, and every cell of one of the colours holds it.
+///
+/// If two cells of the same colour see each other, that colour can not hold the symbol.
+/// Otherwise any cell seeing both colours can not hold the symbol.
+pub(crate) fn simple_coloring(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running Simple Coloring, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let graph = LinkGraph::new(sudoku, houses)?;
+    let mut colors: Vec<Option<bool>> = vec![None; graph.nodes.len()];
+    for start in 0..graph.nodes.len() {
+        if colors[start].is_some() {
+            continue;
+        }
+        let component = match graph.color_component(start, &mut colors) {
+            Some(component) if component.len() > 1 => component,
+            _ => continue,
+        };
+        let symbol = graph.nodes[start].1;
+        // The colour of each node in this component.
+        let mut color_of: Vec<Option<bool>> = vec![None; graph.nodes.len()];
+        for &n in &component {
+            color_of[n] = colors[n];
+        }
+        let sees_color =
+            |n: usize, color: bool| graph.weak[n].iter().any(|&m| color_of[m] == Some(color));
+        let wrapped = [true, false].into_iter().find(|&color| {
+            component
+                .iter()
+                .any(|&n| color_of[n] == Some(color) && sees_color(n, color))
+        });
+        let eliminations: Vec<usize> = match wrapped {
+            Some(color) => component
+                .iter()
+                .copied()
+                .filter(|&n| color_of[n] == Some(color))
+                .collect(),
+            None => (0..graph.nodes.len())
+                .filter(|&n| {
+                    graph.nodes[n].1 == symbol
+                        && color_of[n].is_none()
+                        && sees_color(n, true)
+                        && sees_color(n, false)
+                })
+                .collect(),
+        };
+        if graph.eliminate(sudoku, &eliminations)? {
+            debug!(
+                "Found Simple Coloring of {symbol:?} from {:?}",
+                graph.nodes[start].0
+            );
+            let cells: Vec<Cell> = component.iter().map(|&n| graph.nodes[n].0).collect();
+            sudoku.note_regions(&[cells]);
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// An X-Chain is an Alternating Inference Chain using a single symbol.
+pub(crate) fn x_chain(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running X-Chain, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let graph = LinkGraph::new(sudoku, houses)?;
+    graph.find_chain(sudoku, true)
+}
+
+/// An Alternating Inference Chain follows strong links (if one end is false, the other is true)
+/// and weak links (if one end is true, the other is false) in turn, starting and ending
+/// with a strong link. One of the two ends of the chain must be true,
+/// so any candidate which can not be true alongside either end is removed.
+pub(crate) fn aic(sudoku: &mut Sudoku, houses: &HouseSet) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running Alternating Inference Chain, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let graph = LinkGraph::new(sudoku, houses)?;
+    graph.find_chain(sudoku, false)
+}
+
+/// The candidates of the sudoku and the links between them.
+///
+/// Strong links come from conjugate pairs in houses and from cells with two candidates.
+/// Weak links come from candidates sharing a cell, cells seeing each other,
+/// and whatever links the variant constraints add.
+struct LinkGraph {
+    nodes: Vec<Node>,
+    /// If a node is false, each of these is true.
+    strong: Vec<Vec<usize>>,
+    /// If a node is true, each of these is false.
+    weak: Vec<Vec<usize>>,
+}
+
+impl LinkGraph {
+    /// Builds the links between every unsolved candidate.
+    fn new(sudoku: &Sudoku, houses: &HouseSet) -> Result<Self, SudokuError> {
+        let nodes: Vec<Node> = sudoku
+            .indexed_candidates()
+            .into_iter()
+            .flat_map(|(cell, candidates)| candidates.iter().map(move |s| (cell, *s)))
+            .collect();
+        let index: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let mut strong: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+        let mut weak: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+        let link = |links: &mut Vec<Vec<usize>>, a: &Node, b: &Node| {
+            if let (Some(&a), Some(&b)) = (index.get(a), index.get(b)) {
+                if a != b {
+                    links[a].push(b);
+                    links[b].push(a);
+                }
+            }
+        };
+        for house in houses.iter().filter(|h| is_complete_house(sudoku, h)) {
+            for symbol in &sudoku.valid_symbols {
+                if let [a, b] = get_cells_in_house(sudoku, house, symbol)?.as_slice() {
+                    link(&mut strong, &(*a, *symbol), &(*b, *symbol));
+                }
+            }
+        }
+        for (cell, candidates) in sudoku.indexed_candidates() {
+            if let Some((a, b)) = candidates.iter().collect_tuple() {
+                link(&mut strong, &(cell, *a), &(cell, *b));
+            }
+        }
+        let peers = sudoku.get_peers();
+        for (i, (cell, symbol)) in nodes.iter().enumerate() {
+            let candidates = &sudoku.get_cell(cell)?.0;
+            for other in candidates.iter().filter(|s| *s != symbol) {
+                link(&mut weak, &nodes[i], &(*cell, *other));
+            }
+            for peer in peers
+                .of(cell)
+                .iter()
+                .filter(|p| (p.row, p.col) > (cell.row, cell.col))
+            {
+                link(&mut weak, &nodes[i], &(*peer, *symbol));
+            }
+        }
+        for constraint in &sudoku.constraints {
+            for (a, b) in constraint.get_weak_links(sudoku) {
+                link(&mut weak, &a, &b);
+            }
+        }
+        for links in strong.iter_mut().chain(weak.iter_mut()) {
+            links.sort_unstable();
+            links.dedup();
+        }
+        Ok(Self {
+            nodes,
+            strong,
+            weak,
+        })
+    }
+
+    /// Colours every node joined to `start` by strong links on the same symbol, alternating the
+    /// colour each link, and returns them. Gives up if a node would need both colours.
+    fn color_component(&self, start: usize, colors: &mut [Option<bool>]) -> Option<Vec<usize>> {
+        let mut component = vec![start];
+        let mut queue = VecDeque::from([start]);
+        colors[start] = Some(true);
+        while let Some(n) = queue.pop_front() {
+            let color = !colors[n]?;
+            for &m in self.strong[n]
+                .iter()
+                .filter(|&&m| self.nodes[m].1 == self.nodes[n].1)
+            {
+                match colors[m] {
+                    Some(c) if c != color => return None,
+                    Some(_) => {}
+                    None => {
+                        colors[m] = Some(color);
+                        component.push(m);
+                        queue.push_back(m);
+                    }
+                }
+            }
+        }
+        Some(component)
+    }
+
+    /// Looks for a chain from each candidate, assuming it is false and following the links out
+    /// from there. Every candidate reached as true is the other end of a chain.
+    /// With `single_symbol` set, only candidates of the starting symbol are followed.
+    ///
+    /// Applies the eliminations of the first chain with any.
+    fn find_chain(
+        &self,
+        sudoku: &mut Sudoku,
+        single_symbol: bool,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let mut sees_start = vec![false; self.nodes.len()];
+        for start in (0..self.nodes.len()).filter(|&n| !self.strong[n].is_empty()) {
+            for &n in &self.weak[start] {
+                sees_start[n] = true;
+            }
+            // The state each node was first reached from, as false and as true.
+            let mut reached: Vec<[Option<State>; 2]> = vec![[None; 2]; self.nodes.len()];
+            reached[start][0] = Some((start, false));
+            let mut queue = VecDeque::from([(start, false)]);
+            while let Some((n, is_true)) = queue.pop_front() {
+                let links = match is_true {
+                    true => &self.weak[n],
+                    false => &self.strong[n],
+                };
+                for &m in links {
+                    if single_symbol && self.nodes[m].1 != self.nodes[start].1 {
+                        continue;
+                    }
+                    let m_is_true = !is_true;
+                    if reached[m][m_is_true as usize].is_some() {
+                        continue;
+                    }
+                    reached[m][m_is_true as usize] = Some((n, is_true));
+                    queue.push_back((m, m_is_true));
+                    if !m_is_true {
+                        continue;
+                    }
+                    if m == start {
+                        // Assuming the start is false proves it true.
+                        let (cell, symbol) = &self.nodes[start];
+                        debug!("Found a chain proving {symbol:?} in {cell:?}");
+                        sudoku.place_digit(cell, symbol)?;
+                        sudoku.note_regions(&[get_chain_cells(&self.nodes, &reached, m)]);
+                        return Ok(true);
+                    }
+                    let eliminations: Vec<usize> = self.weak[m]
+                        .iter()
+                        .copied()
+                        .filter(|&c| sees_start[c] && c != start)
+                        .collect();
+                    if self.eliminate(sudoku, &eliminations)? {
+                        debug!(
+                            "Found a chain from {:?} to {:?}",
+                            self.nodes[start], self.nodes[m]
+                        );
+                        sudoku.note_regions(&[get_chain_cells(&self.nodes, &reached, m)]);
+                        return Ok(true);
+                    }
+                }
+            }
+            for &n in &self.weak[start] {
+                sees_start[n] = false;
+            }
+        }
+        Ok(false)
+    }
+
+    fn eliminate(
+        &self,
+        sudoku: &mut Sudoku,
+        nodes: &[usize],
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        let mut did_update = false;
+        for &n in nodes {
+            let (cell, symbol) = &self.nodes[n];
+            trace!("Chain removing {symbol:?} from {cell:?}");
+            did_update |= sudoku.remove_candidate(cell, symbol)?;
+        }
+        Ok(did_update)
+    }
+}
+
+/// The cells along the chain ending with `end` true, found by walking back to its start.
+fn get_chain_cells(nodes: &[Node], reached: &[[Option<State>; 2]], end: usize) -> Vec<Cell> {
+    let mut cells = vec![];
+    let mut state = (end, true);
+    while let Some(from) = reached[state.0][state.1 as usize] {
+        if !cells.contains(&nodes[state.0].0) {
+            cells.push(nodes[state.0].0);
+        }
+        if from == state {
+            break;
+        }
+        state = from;
+    }
+    cells
+}
@@ -1,27 +1,109 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
+use grid::Grid;
 use itertools::Itertools;
 use log::{debug, trace};
 
 use crate::{
     board::{
-        constraints::standard::{get_cells_in_house, get_house_candidates, House, HouseSet},
-        digit::Symbol,
+        constraints::standard::{
+            get_all_houses, get_cells_in_house, get_house_candidates, is_complete_house, House,
+            HouseSet, HouseUnique,
+        },
+        digit::{Candidates, Symbol},
+        solver::{
+            als::{als_xy_wing, als_xz},
+            chains::{aic, simple_coloring, x_chain},
+            wing::{w_wing, xy_wing, xyz_wing},
+        },
+        strategy::Strategy,
         sudoku::{Cell, DidUpdateGrid},
     },
     errors::SudokuError,
     Sudoku,
 };
 
-type HouseStrategy =
+pub(crate) type HouseStrategy =
     fn(sudoku: &mut Sudoku, houses: &HouseSet) -> Result<DidUpdateGrid, SudokuError>;
 
-pub(crate) const HOUSE_STRATEGIES: &[(HouseStrategy, f32)] = &[
-    (hidden_single, 1.5),
-    (locked_candidate, 2.5),
-    (hidden_subset, 3.0),
+pub(crate) const HOUSE_STRATEGIES: &[HouseTechnique] = &[
+    HouseTechnique::new("Hidden Single", hidden_single, 1.5),
+    HouseTechnique::new("Locked Candidate", locked_candidate, 2.5),
+    HouseTechnique::new("Naked Subset", naked_subset, 2.8),
+    HouseTechnique::new("Hidden Subset", hidden_subset, 3.0),
+    HouseTechnique::new("X-Wing", x_wing, 3.2),
+    HouseTechnique::new("Swordfish", swordfish, 3.8),
+    HouseTechnique::new("Finned Fish", finned_fish, 4.0),
+    HouseTechnique::new("XY-Wing", xy_wing, 4.2),
+    HouseTechnique::new("XYZ-Wing", xyz_wing, 4.4),
+    HouseTechnique::new("W-Wing", w_wing, 4.4),
+    HouseTechnique::new("Simple Coloring", simple_coloring, 4.6),
+    HouseTechnique::new("Jellyfish", jellyfish, 5.2),
+    HouseTechnique::new("X-Chain", x_chain, 6.5),
+    HouseTechnique::new("Alternating Inference Chain", aic, 7.0),
+    HouseTechnique::new("ALS-XZ", als_xz, 7.5),
+    HouseTechnique::new("ALS-XY-Wing", als_xy_wing, 8.0),
 ];
 
+/// A strategy over every house of the sudoku, as registered with a `SolverConfig`.
+#[derive(Clone, Copy)]
+pub(crate) struct HouseTechnique {
+    name: &'static str,
+    strategy: HouseStrategy,
+    weight: f32,
+    /// Whether the strategy is only sound for a sudoku with a single solution.
+    needs_unique: bool,
+}
+
+impl HouseTechnique {
+    const fn new(name: &'static str, strategy: HouseStrategy, weight: f32) -> Self {
+        Self {
+            name,
+            strategy,
+            weight,
+            needs_unique: false,
+        }
+    }
+
+    /// A strategy which only runs when the sudoku assumes it has a single solution.
+    pub(crate) const fn assuming_unique(
+        name: &'static str,
+        strategy: HouseStrategy,
+        weight: f32,
+    ) -> Self {
+        Self {
+            needs_unique: true,
+            ..Self::new(name, strategy, weight)
+        }
+    }
+}
+
+impl Strategy for HouseTechnique {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    fn is_applicable(&self, sudoku: &Sudoku) -> bool {
+        (sudoku.assume_unique || !self.needs_unique)
+            && sudoku
+                .constraints
+                .iter()
+                .any(|c| c.as_any().is::<HouseUnique>())
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        let houses = get_all_houses(sudoku);
+        (self.strategy)(sudoku, &houses)
+    }
+}
+
+/// The most fins a finned fish may have.
+const MAX_FINS: usize = 2;
+
 pub(crate) fn hidden_single(
     sudoku: &mut Sudoku,
     houses: &HouseSet,
@@ -34,6 +116,7 @@ pub(crate) fn hidden_single(
     for house in houses {
         let did_update = hidden_single_house(sudoku, house)?;
         if did_update {
+            sudoku.note_regions(&[house]);
             return Ok(true);
         }
     }
@@ -41,6 +124,9 @@ pub(crate) fn hidden_single(
 }
 
 fn hidden_single_house(sudoku: &mut Sudoku, house: &House) -> Result<DidUpdateGrid, SudokuError> {
+    if !is_complete_house(sudoku, house) {
+        return Ok(false);
+    }
     let mut did_update = false;
     let mut digit_count: HashMap<Symbol, Vec<Cell>> = HashMap::new();
     for cell in house {
@@ -74,14 +160,16 @@ pub(crate) fn locked_candidate(
         sudoku.get_entropy(),
         sudoku.to_string_line()
     );
-    let combinations = houses.iter().combinations(2);
-    for t in combinations {
+    // The check is one way, from the first house into the second, so both orders are tried.
+    let permutations = houses.iter().permutations(2);
+    for t in permutations {
         let (house1, house2) = match t.as_slice() {
             [h1, h2] => (h1, h2),
             _ => continue,
         };
         let did_update = locked_candidate_houses(sudoku, house1, house2)?;
         if did_update {
+            sudoku.note_regions(&[house1, house2]);
             return Ok(true);
         }
     }
@@ -95,6 +183,9 @@ fn locked_candidate_houses(
     house1: &House,
     house2: &House,
 ) -> Result<DidUpdateGrid, SudokuError> {
+    if !is_complete_house(sudoku, house1) {
+        return Ok(false);
+    }
     let candidates1 = get_house_candidates(sudoku, house1)?;
     for symbol in candidates1 {
         let h1_candidate_cells = get_cells_in_house(sudoku, house1, &symbol)?;
@@ -118,6 +209,77 @@ fn locked_candidate_houses(
     Ok(false)
 }
 
+pub(crate) fn naked_subset(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running Naked Subset, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let max_house_size = houses.iter().map(|h| h.len()).max().unwrap_or(0);
+    for subset_size in 2..=max_house_size / 2 {
+        for house in houses {
+            let did_update = naked_subset_house(sudoku, house, subset_size)?;
+            if did_update {
+                sudoku.note_regions(&[house]);
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// If `n` unsolved cells within a house only have `n` candidates between them,
+/// those candidates must be placed in those cells, so they can be removed from the rest of the house.
+fn naked_subset_house(
+    sudoku: &mut Sudoku,
+    house: &House,
+    num: usize,
+) -> Result<DidUpdateGrid, SudokuError> {
+    let unsolved: Vec<(Cell, Candidates)> = house
+        .iter()
+        .filter_map(|c| {
+            sudoku
+                .get_cell(c)
+                .ok()
+                .and_then(|d| d.try_get_candidates())
+                .map(|candidates| (*c, candidates.clone()))
+        })
+        .collect();
+    // A subset of every unsolved cell leaves nothing to remove from.
+    if unsolved.len() <= num {
+        return Ok(false);
+    }
+    // Cells with more than `num` candidates can never be part of the subset.
+    let small = unsolved.iter().filter(|(_, s)| s.len() <= num);
+    for combo in small.combinations(num) {
+        let Some(symbols) = combo
+            .iter()
+            .map(|(_, s)| s.clone())
+            .reduce(|a, b| a.union(&b))
+        else {
+            continue;
+        };
+        if symbols.len() != num {
+            continue;
+        }
+        trace!("Found Naked Subset {symbols:?} in {combo:?}");
+        let mut did_update = false;
+        for (cell, _) in unsolved.iter().filter(|u| !combo.contains(u)) {
+            for symbol in &symbols {
+                did_update |= sudoku.remove_candidate(cell, symbol)?;
+            }
+        }
+        if did_update {
+            debug!("Naked Subset {symbols:?} did_update {did_update}");
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 pub(crate) fn hidden_subset(
     sudoku: &mut Sudoku,
     houses: &HouseSet,
@@ -140,6 +302,7 @@ pub(crate) fn hidden_subset(
         for house in houses {
             let did_update = hidden_subset_house(sudoku, house, subset_size)?;
             if did_update {
+                sudoku.note_regions(&[house]);
                 return Ok(true);
             }
         }
@@ -154,31 +317,330 @@ fn hidden_subset_house(
     house: &House,
     num: usize,
 ) -> Result<DidUpdateGrid, SudokuError> {
-    let did_update = false;
+    if !is_complete_house(sudoku, house) {
+        return Ok(false);
+    }
     let candidates = get_house_candidates(sudoku, house)?;
+    let unsolved: Vec<(Cell, Candidates)> = house
+        .iter()
+        .filter_map(|c| {
+            sudoku
+                .get_cell(c)
+                .ok()
+                .and_then(|d| d.try_get_candidates())
+                .map(|candidates| (*c, candidates.clone()))
+        })
+        .collect();
     let combinations = candidates.iter().combinations(num);
     for combo in combinations {
         trace!("Attempting Combo {combo:?}");
         // Check that the combo exist in the same `num` cells
-        let found_cells_vec = combo
+        let combo_set = candidates.with_symbols(combo.iter().copied());
+        let found_cells: Vec<Cell> = unsolved
             .iter()
-            .map(|s| get_cells_in_house(sudoku, house, s))
-            .collect::<Result<Vec<_>, _>>()?;
-        let found_cells: HashSet<_> = found_cells_vec.into_iter().flatten().collect();
+            .filter(|(_, c)| c.intersects(&combo_set))
+            .map(|(cell, _)| *cell)
+            .collect();
         if found_cells.len() != num {
             // If the number of cells found is not equal to the number of candidates, skip
             continue;
         }
         // Found a match - we can remove all other candidates from these cells.
         debug!("Found a Subset!");
-        let combo_symbols: Vec<Symbol> = combo.into_iter()
-            .map(|s| s.clone())
-            .collect();
-        sudoku.keep_candidates(found_cells, &combo_symbols)?;
+        let combo_symbols: Vec<Symbol> = combo.into_iter().copied().collect();
+        let did_update = sudoku.keep_candidates(found_cells, &combo_symbols)?;
         debug!("Subset did_update {did_update}");
         if did_update {
             return Ok(true);
         }
     }
-    Ok(did_update)
+    Ok(false)
+}
+
+pub(crate) fn x_wing(sudoku: &mut Sudoku, houses: &HouseSet) -> Result<DidUpdateGrid, SudokuError> {
+    fish(sudoku, houses, 2, 0)
+}
+
+pub(crate) fn swordfish(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    fish(sudoku, houses, 3, 0)
+}
+
+pub(crate) fn jellyfish(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    fish(sudoku, houses, 4, 0)
+}
+
+/// Finned (and sashimi) X-Wings and Swordfish.
+///
+/// Finned Jellyfish are left out: they are far slower to search, and rated well above
+/// the weight of this strategy.
+pub(crate) fn finned_fish(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    for size in 2..=3 {
+        if fish(sudoku, houses, size, MAX_FINS)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// A fish takes `size` base houses, which each still need the symbol and share no cells,
+/// and `size` other cover houses, which share no cells either, containing every base candidate.
+/// The base houses place `size` copies of the symbol, one in each cover house,
+/// so the symbol can be removed from the rest of the cover houses.
+///
+/// Any house can be a base or a cover, so jigsaw regions and diagonals take part too.
+///
+/// Base candidates outside the cover houses are fins. If one of them holds the symbol the fish
+/// falls apart, so only the cells which also see every fin lose the symbol.
+/// A sashimi fish is a finned fish where a base house has at most one candidate left once the fins are gone.
+fn fish(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+    size: usize,
+    max_fins: usize,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running Fish of size {size} with up to {max_fins} fins, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let houses: Vec<&House> = houses.iter().collect();
+    let (rows, cols) = sudoku.size();
+    let mut cell_houses: Grid<Vec<usize>> = Grid::new(rows, cols);
+    for (i, house) in houses.iter().enumerate() {
+        for cell in house.iter() {
+            cell_houses[(cell.row, cell.col)].push(i);
+        }
+    }
+    let mut overlaps: Grid<bool> = Grid::new(houses.len(), houses.len());
+    for in_cell in cell_houses.iter() {
+        for (&a, &b) in in_cell.iter().cartesian_product(in_cell) {
+            overlaps[(a, b)] = true;
+        }
+    }
+    let symbols: Vec<Symbol> = sudoku.all_candidates.iter().copied().collect();
+    for symbol in symbols {
+        let candidates = houses
+            .iter()
+            .map(|h| get_cells_in_house(sudoku, h, &symbol))
+            .collect::<Result<Vec<_>, _>>()?;
+        let bases: Vec<usize> = (0..houses.len())
+            .filter(|&i| {
+                (2..=size + max_fins).contains(&candidates[i].len())
+                    && is_complete_house(sudoku, houses[i])
+                    && !houses[i].iter().any(|c| {
+                        sudoku
+                            .get_cell(c)
+                            .is_ok_and(|d| d.try_get_solved() == Some(&symbol))
+                    })
+            })
+            .collect();
+        let search = FishSearch {
+            houses: &houses,
+            cell_houses: &cell_houses,
+            overlaps: &overlaps,
+            candidates: &candidates,
+            bases: &bases,
+            size,
+            max_fins,
+        };
+        let (targets, fish_houses) = match search.find_bases(0, &mut vec![]) {
+            Some(found) => found,
+            None => continue,
+        };
+        debug!("Found Fish of {symbol:?}, removing it from {targets:?}");
+        let mut did_update = false;
+        for cell in &targets {
+            did_update |= sudoku.remove_candidate(cell, &symbol)?;
+        }
+        if did_update {
+            let fish_houses: Vec<&House> = fish_houses.iter().map(|&h| houses[h]).collect();
+            sudoku.note_regions(&fish_houses);
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// The cells a fish removes its symbol from, and the index of each of its houses.
+type FishFound = (Vec<Cell>, Vec<usize>);
+
+struct FishSearch<'a> {
+    houses: &'a [&'a House],
+    /// The index of every house each cell is in.
+    cell_houses: &'a Grid<Vec<usize>>,
+    /// Whether each pair of houses shares a cell.
+    overlaps: &'a Grid<bool>,
+    /// The candidates for the symbol in each house.
+    candidates: &'a [Vec<Cell>],
+    /// The houses which can be a base.
+    bases: &'a [usize],
+    size: usize,
+    max_fins: usize,
+}
+
+impl FishSearch<'_> {
+    /// Picks base houses which share no cells, then looks for covers once there are enough.
+    fn find_bases(&self, start: usize, chosen: &mut Vec<usize>) -> Option<FishFound> {
+        if chosen.len() == self.size {
+            return self.find_covers(chosen);
+        }
+        for k in start..self.bases.len() {
+            let base = self.bases[k];
+            if chosen.iter().any(|&h| self.overlaps[(base, h)]) {
+                continue;
+            }
+            chosen.push(base);
+            let found = self.find_bases(k + 1, chosen);
+            chosen.pop();
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    /// Works out which base cells each other house covers,
+    /// as bit masks over the base cells to keep the cover search cheap.
+    fn find_covers(&self, bases: &[usize]) -> Option<FishFound> {
+        let base_cells: Vec<Cell> = bases
+            .iter()
+            .flat_map(|&b| &self.candidates[b])
+            .copied()
+            .collect();
+        let mut masks = vec![0u32; self.houses.len()];
+        for (j, cell) in base_cells.iter().enumerate() {
+            for &h in self.get_houses(cell).iter().filter(|h| !bases.contains(h)) {
+                masks[h] |= 1 << j;
+            }
+        }
+        let covers: Vec<(usize, u32)> = masks
+            .into_iter()
+            .enumerate()
+            .filter(|(_, mask)| *mask != 0)
+            .collect();
+        let search = CoverSearch {
+            fish: self,
+            base_cells: &base_cells,
+            covers: &covers,
+        };
+        let (targets, mut covers) = search.find(0, &mut vec![], &mut vec![], 0)?;
+        covers.extend(bases);
+        Some((targets, covers))
+    }
+
+    fn get_houses(&self, cell: &Cell) -> &[usize] {
+        &self.cell_houses[(cell.row, cell.col)]
+    }
+
+    fn sees(&self, a: &Cell, b: &Cell) -> bool {
+        a != b
+            && self
+                .get_houses(a)
+                .iter()
+                .any(|h| self.get_houses(b).contains(h))
+    }
+}
+
+struct CoverSearch<'a> {
+    fish: &'a FishSearch<'a>,
+    base_cells: &'a [Cell],
+    /// Every house which could be a cover, with the mask of base cells it contains.
+    covers: &'a [(usize, u32)],
+}
+
+impl CoverSearch<'_> {
+    /// Chooses covers for the base cells one at a time, or leaves them as fins,
+    /// returning the cells the first fish with any eliminations removes the symbol from,
+    /// along with its covers.
+    fn find(
+        &self,
+        covered: u32,
+        chosen: &mut Vec<usize>,
+        tried: &mut Vec<usize>,
+        fins: u32,
+    ) -> Option<FishFound> {
+        let all = (1u32 << self.base_cells.len()) - 1;
+        let uncovered = all & !covered & !fins;
+        if uncovered == 0 {
+            return self.get_targets(chosen, fins);
+        }
+        // The covers left can not take in more cells than the biggest of them times how many there are.
+        let spare_fins = self.fish.max_fins - fins.count_ones() as usize;
+        let most_covered = self
+            .covers
+            .iter()
+            .filter(|(_, mask)| mask & fins == 0)
+            .map(|(_, mask)| (mask & uncovered).count_ones() as usize)
+            .max()
+            .unwrap_or(0);
+        let spare_covers = self.fish.size - chosen.len();
+        if most_covered * spare_covers + spare_fins < uncovered.count_ones() as usize {
+            return None;
+        }
+        let cell = uncovered.trailing_zeros();
+        // Covers tried for an earlier cell, or a fin, have already been searched with and without.
+        // Covers may not share cells either, just like the bases.
+        let tried_before = tried.len();
+        let mut found = None;
+        if chosen.len() < self.fish.size {
+            for (i, (house, mask)) in self.covers.iter().enumerate() {
+                if mask & (1 << cell) == 0 || mask & fins != 0 || tried.contains(&i) {
+                    continue;
+                }
+                if chosen
+                    .iter()
+                    .any(|&j| self.fish.overlaps[(*house, self.covers[j].0)])
+                {
+                    continue;
+                }
+                chosen.push(i);
+                tried.push(i);
+                found = self.find(covered | mask, chosen, tried, fins);
+                chosen.pop();
+                if found.is_some() {
+                    break;
+                }
+            }
+        }
+        // Only cells seeing every fin lose the symbol, so the fins have to see each other.
+        let new_fin = &self.base_cells[cell as usize];
+        if found.is_none()
+            && spare_fins > 0
+            && self.get_fins(fins).all(|f| self.fish.sees(f, new_fin))
+        {
+            found = self.find(covered, chosen, tried, fins | 1 << cell);
+        }
+        tried.truncate(tried_before);
+        found
+    }
+
+    fn get_fins(&self, fins: u32) -> impl Iterator<Item = &Cell> {
+        (0..self.base_cells.len())
+            .filter(move |j| fins & (1 << j) != 0)
+            .map(|j| &self.base_cells[j])
+    }
+
+    fn get_targets(&self, chosen: &[usize], fins: u32) -> Option<FishFound> {
+        let fins: Vec<&Cell> = self.get_fins(fins).collect();
+        let targets: Vec<Cell> = chosen
+            .iter()
+            .flat_map(|&i| &self.fish.candidates[self.covers[i].0])
+            .filter(|c| !self.base_cells.contains(c))
+            .filter(|c| fins.iter().all(|f| self.fish.sees(c, f)))
+            .copied()
+            .unique()
+            .collect();
+        trace!("Fish covers {chosen:?} with fins {fins:?} removes from {targets:?}");
+        let covers = chosen.iter().map(|&i| self.covers[i].0).collect();
+        (!targets.is_empty()).then_some((targets, covers))
+    }
 }
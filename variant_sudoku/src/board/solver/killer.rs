@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use log::{debug, trace};
+
+use crate::{
+    board::{
+        constraints::{
+            killer::{get_all_cages, Cage, Killer, KillerMarking},
+            standard::{is_complete_house, House, HouseSet},
+        },
+        strategy::Strategy,
+        sudoku::{Cell, DidUpdateGrid},
+    },
+    errors::SudokuError,
+    Sudoku,
+};
+
+type KillerStrategy = fn(sudoku: &mut Sudoku, cages: &[Cage]) -> Result<DidUpdateGrid, SudokuError>;
+
+pub(crate) const KILLER_STRATEGIES: &[KillerTechnique] = &[KillerTechnique {
+    name: "Innies and Outies",
+    strategy: innies_outies,
+    weight: 3.0,
+}];
+
+/// A strategy over the cages of every killer constraint, as registered with a `SolverConfig`.
+#[derive(Clone, Copy)]
+pub(crate) struct KillerTechnique {
+    name: &'static str,
+    strategy: KillerStrategy,
+    weight: f32,
+}
+
+impl Strategy for KillerTechnique {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    fn is_applicable(&self, sudoku: &Sudoku) -> bool {
+        sudoku.constraints.iter().any(|c| c.as_any().is::<Killer>())
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError> {
+        let cages = get_all_cages(sudoku);
+        (self.strategy)(sudoku, &cages)
+    }
+}
+
+/// Virtual cages larger than this are not worth searching, as they rarely rule anything out.
+const MAX_VIRTUAL_CAGE_SIZE: usize = 4;
+
+/// The 45 rule, generalised to any set of complete houses that do not overlap.
+///
+/// The digits of `n` complete houses always add up to `n` times the total of the symbols.
+/// Taking away every cage that lies inside those houses leaves the innies, a virtual cage
+/// with a known sum. If every innie is in a cage poking out of the houses, the cells those
+/// cages leave outside (the outies) form a virtual cage with a known sum too.
+pub(crate) fn innies_outies(
+    sudoku: &mut Sudoku,
+    cages: &[Cage],
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running Innies and Outies, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let house_total: u32 = match sudoku
+        .valid_symbols
+        .iter()
+        .map(|s| sudoku.get_value(s))
+        .sum()
+    {
+        Some(total) => total,
+        None => return Ok(false),
+    };
+    let houses = sudoku.get_houses();
+    let cages: Vec<(&Cage, u32)> = cages
+        .iter()
+        .filter_map(|c| c.get_sum().map(|sum| (c, sum)))
+        .collect();
+    for (region, num_houses) in get_house_unions(sudoku, &houses) {
+        let region_total = house_total * num_houses as u32;
+        if let Some((innies, innie_sum)) = get_innies(&region, region_total, &cages) {
+            if prune_virtual_cage(sudoku, &innies, innie_sum)? {
+                debug!("Innies {innies:?} of {region:?} must add up to {innie_sum}");
+                sudoku.note_regions(&[&innies]);
+                return Ok(true);
+            }
+            if let Some((outies, outie_sum)) = get_outies(&region, &innies, innie_sum, &cages) {
+                if prune_virtual_cage(sudoku, &outies, outie_sum)? {
+                    debug!("Outies {outies:?} of {region:?} must add up to {outie_sum}");
+                    sudoku.note_regions(&[&outies]);
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Every run of consecutive complete rows or columns, every other complete house on its own,
+/// and every run of boxes along a band or a stack, along with the number of houses making it up.
+fn get_house_unions(sudoku: &Sudoku, houses: &HouseSet) -> Vec<(HashSet<Cell>, usize)> {
+    let complete: Vec<&House> = houses
+        .iter()
+        .filter(|h| is_complete_house(sudoku, h))
+        .collect();
+    let (rows, cols) = sudoku.size();
+    let line = |is_line: &dyn Fn(&House, usize) -> bool, count: usize| -> Vec<Option<&House>> {
+        (0..count)
+            .map(|i| complete.iter().find(|h| is_line(h, i)).copied())
+            .collect()
+    };
+    let row_houses = line(
+        &|h, r| h.len() == cols && h.iter().all(|c| c.row == r),
+        rows,
+    );
+    let col_houses = line(
+        &|h, c| h.len() == rows && h.iter().all(|x| x.col == c),
+        cols,
+    );
+    let mut unions = vec![];
+    for lines in [&row_houses, &col_houses] {
+        for start in 0..lines.len() {
+            let mut region = HashSet::new();
+            for (end, house) in lines.iter().enumerate().skip(start) {
+                let house = match house {
+                    Some(h) => h,
+                    None => break,
+                };
+                region.extend(house.iter().copied());
+                unions.push((region.clone(), end - start + 1));
+            }
+        }
+    }
+    let others: Vec<&House> = complete
+        .into_iter()
+        .filter(|h| !row_houses.contains(&Some(h)) && !col_houses.contains(&Some(h)))
+        .collect();
+    for house in &others {
+        unions.push((house.iter().copied().collect(), 1));
+    }
+    unions.extend(get_box_runs(&others));
+    unions
+}
+
+/// Every run of two or more boxes next to each other along a band or a stack,
+/// along with the number of boxes making it up.
+///
+/// Only houses filling a rectangle count as boxes, so jigsaw regions are left alone.
+fn get_box_runs(houses: &[&House]) -> Vec<(HashSet<Cell>, usize)> {
+    // The first and last row, then the first and last column, of each box.
+    let boxes: Vec<(&House, [usize; 4])> = houses
+        .iter()
+        .filter_map(|h| {
+            let bounds = [
+                h.iter().map(|c| c.row).min()?,
+                h.iter().map(|c| c.row).max()?,
+                h.iter().map(|c| c.col).min()?,
+                h.iter().map(|c| c.col).max()?,
+            ];
+            let area = (bounds[1] - bounds[0] + 1) * (bounds[3] - bounds[2] + 1);
+            (area == h.len()).then_some((*h, bounds))
+        })
+        .collect();
+    let mut runs = vec![];
+    // A band shares its rows and runs across the columns, a stack the other way around.
+    for (across, along) in [((0, 1), (2, 3)), ((2, 3), (0, 1))] {
+        let lines = boxes
+            .iter()
+            .into_group_map_by(|(_, b)| (b[across.0], b[across.1]));
+        for (_, mut line) in lines.into_iter().sorted_by_key(|(key, _)| *key) {
+            line.sort_by_key(|(_, b)| b[along.0]);
+            for start in 0..line.len() {
+                let mut region: HashSet<Cell> = line[start].0.iter().copied().collect();
+                for end in start + 1..line.len() {
+                    if line[end].1[along.0] != line[end - 1].1[along.1] + 1 {
+                        break;
+                    }
+                    region.extend(line[end].0.iter().copied());
+                    runs.push((region.clone(), end - start + 1));
+                }
+            }
+        }
+    }
+    runs
+}
+
+/// The cells of `region` not covered by a cage lying inside it, and what they add up to.
+fn get_innies(
+    region: &HashSet<Cell>,
+    region_total: u32,
+    cages: &[(&Cage, u32)],
+) -> Option<(Vec<Cell>, u32)> {
+    let mut covered: HashSet<Cell> = HashSet::new();
+    let mut caged_sum = 0;
+    for (cage, sum) in cages {
+        let is_inside = cage.cells.iter().all(|c| region.contains(c));
+        if is_inside && cage.cells.iter().all(|c| !covered.contains(c)) {
+            covered.extend(cage.cells.iter().copied());
+            caged_sum += sum;
+        }
+    }
+    let mut innies: Vec<Cell> = region.difference(&covered).copied().collect();
+    innies.sort_by_key(|c| (c.row, c.col));
+    let innie_sum = region_total.checked_sub(caged_sum)?;
+    trace!("Innies {innies:?} add up to {innie_sum}");
+    (!innies.is_empty()).then_some((innies, innie_sum))
+}
+
+/// The cells outside `region` belonging to cages which cover every innie, and what they add up to.
+fn get_outies(
+    region: &HashSet<Cell>,
+    innies: &[Cell],
+    innie_sum: u32,
+    cages: &[(&Cage, u32)],
+) -> Option<(Vec<Cell>, u32)> {
+    let mut crossing: Vec<&(&Cage, u32)> = vec![];
+    for innie in innies {
+        let cage = cages.iter().find(|(c, _)| c.cells.contains(innie))?;
+        if !crossing.iter().any(|(c, _)| c == &cage.0) {
+            crossing.push(cage);
+        }
+    }
+    let (inside, mut outies): (Vec<Cell>, Vec<Cell>) = crossing
+        .iter()
+        .flat_map(|(c, _)| c.cells.iter().copied())
+        .partition(|cell| region.contains(cell));
+    // Overlapping cages would count some cells twice.
+    if inside.len() != innies.len() || outies.iter().collect::<HashSet<_>>().len() != outies.len() {
+        return None;
+    }
+    outies.sort_by_key(|c| (c.row, c.col));
+    let crossing_sum: u32 = crossing.iter().map(|(_, sum)| sum).sum();
+    let outie_sum = crossing_sum.checked_sub(innie_sum)?;
+    trace!("Outies {outies:?} add up to {outie_sum}");
+    (!outies.is_empty()).then_some((outies, outie_sum))
+}
+
+/// Removes the candidates of `cells` which can not be part of any way of adding up to `sum`.
+///
+/// The cells are treated as a cage which is not unique, as digits only have to differ
+/// between cells sharing a house.
+fn prune_virtual_cage(
+    sudoku: &mut Sudoku,
+    cells: &[Cell],
+    sum: u32,
+) -> Result<DidUpdateGrid, SudokuError> {
+    if cells.len() > MAX_VIRTUAL_CAGE_SIZE {
+        return Ok(false);
+    }
+    Cage::new(cells.to_vec(), KillerMarking::Sum(sum), false).notify_cage(sudoku)
+}
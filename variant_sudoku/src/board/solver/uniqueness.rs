@@ -0,0 +1,308 @@
+use itertools::Itertools;
+use log::debug;
+
+use crate::{
+    board::{
+        constraints::standard::{
+            get_cells_in_house, is_complete_house, House, HouseSet, HouseUnique,
+        },
+        digit::{Candidates, Symbol},
+        solver::house::HouseTechnique,
+        sudoku::{Cell, DidUpdateGrid, Peers},
+    },
+    errors::SudokuError,
+    Sudoku,
+};
+
+/// Strategies which are only sound for a puzzle with a single solution,
+/// so they only run when the sudoku assumes one.
+pub(crate) const UNIQUENESS_STRATEGIES: &[HouseTechnique] = &[
+    HouseTechnique::assuming_unique("Unique Rectangle", unique_rectangle, 4.5),
+    HouseTechnique::assuming_unique("BUG+1", bug_plus_one, 5.6),
+];
+
+/// The pairs of corners making up each side of a rectangle, ordered so that
+/// the opposite of side `i` is side `i ^ 1`.
+const SIDES: [[usize; 2]; 4] = [[0, 1], [2, 3], [0, 2], [1, 3]];
+
+/// Whether swapping two symbols around `rectangle` leaves `region` (a house or a cage) with the
+/// same symbols. That is when it misses the rectangle, or holds one side of it.
+pub(crate) fn keeps_swap(region: &[Cell], rectangle: &[Cell; 4]) -> bool {
+    let inside: Vec<&Cell> = rectangle.iter().filter(|c| region.contains(c)).collect();
+    match inside.as_slice() {
+        [] => true,
+        [a, b] => a.row == b.row || a.col == b.col,
+        _ => false,
+    }
+}
+
+/// A Unique Rectangle is four cells, in two rows and two columns, which could all be either of
+/// the same two symbols. If that were all they could be, the symbols could swap around the
+/// rectangle for a second solution, so something else has to go in one of the cells.
+///
+/// * Type 1: Three corners only have the two symbols, so the fourth can be neither of them.
+/// * Type 2: One side only has the two symbols, and the other side has the same single extra,
+///   which one of its cells must be. It is removed from every cell seeing both.
+/// * Type 3: The extras of the other side act as a single cell, forming a naked subset with
+///   other cells of a house the side is in.
+/// * Type 4: One of the two symbols can only go on the other side within a house the side is
+///   in, so the other symbol can go in neither of its cells.
+pub(crate) fn unique_rectangle(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running Unique Rectangle, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let complete: Vec<&House> = houses
+        .iter()
+        .filter(|h| is_complete_house(sudoku, h))
+        .collect();
+    let peers = sudoku.get_peers();
+    for cells in get_rectangles(sudoku) {
+        let candidates = cells
+            .iter()
+            .map(|c| Ok(sudoku.get_cell(c)?.0.clone()))
+            .collect::<Result<Vec<Candidates>, SudokuError>>()?;
+        let common: Vec<Symbol> = candidates[0]
+            .iter()
+            .filter(|s| candidates.iter().all(|c| c.contains(s)))
+            .copied()
+            .collect();
+        if common.len() < 2
+            || !sudoku
+                .constraints
+                .iter()
+                .all(|c| c.allows_swap(sudoku, &cells))
+        {
+            continue;
+        }
+        for (a, b) in common.into_iter().tuple_combinations() {
+            let rectangle = Rectangle {
+                cells,
+                candidates: &candidates,
+                pair: [a, b],
+            };
+            if rectangle.eliminate(sudoku, &complete, &peers)? {
+                debug!("Found Unique Rectangle of {a:?} and {b:?} on {cells:?}");
+                sudoku.note_regions(&[cells]);
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// A Bivalue Universal Grave is a position where every unsolved cell has two candidates, and
+/// every candidate appears twice in each house it is in. Such a position has either no solution
+/// or more than one, so when a single cell has a third candidate (BUG+1), that cell must be the
+/// candidate appearing three times in its houses.
+///
+/// The argument only holds when houses are the only constraints.
+pub(crate) fn bug_plus_one(
+    sudoku: &mut Sudoku,
+    houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running BUG+1, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    if !sudoku
+        .constraints
+        .iter()
+        .all(|c| c.as_any().is::<HouseUnique>())
+    {
+        return Ok(false);
+    }
+    let unsolved = sudoku.indexed_candidates();
+    let cell = match unsolved
+        .iter()
+        .filter(|(_, c)| c.len() != 2)
+        .collect_vec()
+        .as_slice()
+    {
+        [(cell, candidates)] if candidates.len() == 3 => *cell,
+        _ => return Ok(false),
+    };
+    let mut extra: Option<Symbol> = None;
+    for house in houses.iter().filter(|h| is_complete_house(sudoku, h)) {
+        for symbol in &sudoku.valid_symbols {
+            match get_cells_in_house(sudoku, house, symbol)?.len() {
+                0 | 2 => {}
+                3 if house.contains(&cell) && extra.is_none_or(|e| e == *symbol) => {
+                    extra = Some(*symbol);
+                }
+                _ => return Ok(false),
+            }
+        }
+    }
+    let symbol = match extra {
+        Some(s) => s,
+        None => return Ok(false),
+    };
+    debug!("Found BUG+1, {cell:?} must be {symbol:?}");
+    sudoku.note_regions(&[[cell]]);
+    sudoku.place_digit(&cell, &symbol)
+}
+
+/// Every rectangle of four unsolved cells.
+fn get_rectangles(sudoku: &Sudoku) -> Vec<[Cell; 4]> {
+    let (rows, cols) = sudoku.size();
+    let is_unsolved = |row: usize, col: usize| {
+        sudoku
+            .get_cell(&Cell { row, col })
+            .is_ok_and(|d| !d.is_solved())
+    };
+    let mut rectangles = vec![];
+    for (r1, r2) in (0..rows).tuple_combinations() {
+        for (c1, c2) in (0..cols).tuple_combinations() {
+            let corners = [(r1, c1), (r1, c2), (r2, c1), (r2, c2)];
+            if corners.iter().all(|&(r, c)| is_unsolved(r, c)) {
+                rectangles.push(corners.map(|(row, col)| Cell { row, col }));
+            }
+        }
+    }
+    rectangles
+}
+
+struct Rectangle<'a> {
+    cells: [Cell; 4],
+    candidates: &'a [Candidates],
+    /// The two symbols which could swap around the rectangle.
+    pair: [Symbol; 2],
+}
+
+impl Rectangle<'_> {
+    /// Whether the corner only has the two symbols of the rectangle.
+    fn is_pair(&self, corner: usize) -> bool {
+        self.candidates[corner].len() == 2
+    }
+
+    /// The candidates of the corner besides the two symbols of the rectangle.
+    fn get_extras(&self, corner: usize) -> Vec<Symbol> {
+        self.candidates[corner]
+            .iter()
+            .filter(|s| !self.pair.contains(s))
+            .copied()
+            .collect()
+    }
+
+    fn eliminate(
+        &self,
+        sudoku: &mut Sudoku,
+        houses: &[&House],
+        peers: &Peers,
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        if let [corner] = (0..4)
+            .filter(|&i| !self.is_pair(i))
+            .collect_vec()
+            .as_slice()
+        {
+            let mut did_update = false;
+            for symbol in &self.pair {
+                did_update |= sudoku.remove_candidate(&self.cells[*corner], symbol)?;
+            }
+            return Ok(did_update);
+        }
+        for side in 0..SIDES.len() {
+            let floor = SIDES[side];
+            let roof = SIDES[side ^ 1];
+            if !floor.iter().all(|&i| self.is_pair(i)) || roof.iter().any(|&i| self.is_pair(i)) {
+                continue;
+            }
+            let roof_cells = roof.map(|i| self.cells[i]);
+            let extras: Vec<Symbol> = roof
+                .iter()
+                .flat_map(|&i| self.get_extras(i))
+                .unique()
+                .collect();
+            if let [extra] = extras.as_slice() {
+                let mut did_update = false;
+                for cell in peers.common(&roof_cells) {
+                    did_update |= sudoku.remove_candidate(&cell, extra)?;
+                }
+                if did_update {
+                    return Ok(true);
+                }
+            }
+            for house in houses
+                .iter()
+                .filter(|h| roof_cells.iter().all(|c| h.contains(c)))
+            {
+                if self.locked_pair(sudoku, house, &roof_cells)?
+                    || naked_subset_with_extras(sudoku, house, &roof_cells, &extras)?
+                {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Type 4, where one symbol of the pair only goes in the roof within `house`.
+    fn locked_pair(
+        &self,
+        sudoku: &mut Sudoku,
+        house: &House,
+        roof_cells: &[Cell; 2],
+    ) -> Result<DidUpdateGrid, SudokuError> {
+        for (locked, other) in [(self.pair[0], self.pair[1]), (self.pair[1], self.pair[0])] {
+            let cells = get_cells_in_house(sudoku, house, &locked)?;
+            if cells.iter().all(|c| roof_cells.contains(c)) {
+                let mut did_update = false;
+                for cell in roof_cells {
+                    did_update |= sudoku.remove_candidate(cell, &other)?;
+                }
+                return Ok(did_update);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Type 3, where the extras of the roof join `k` other cells of `house` with only `k + 1`
+/// candidates between them, which can then go nowhere else in the house.
+fn naked_subset_with_extras(
+    sudoku: &mut Sudoku,
+    house: &House,
+    roof_cells: &[Cell; 2],
+    extras: &[Symbol],
+) -> Result<DidUpdateGrid, SudokuError> {
+    let others: Vec<(Cell, Candidates)> = house
+        .iter()
+        .filter(|c| !roof_cells.contains(c))
+        .filter_map(|c| {
+            sudoku
+                .get_cell(c)
+                .ok()
+                .and_then(|d| d.try_get_candidates())
+                .map(|candidates| (*c, candidates.clone()))
+        })
+        .collect();
+    for size in 1..=3 {
+        for subset in others.iter().combinations(size) {
+            let symbols: Vec<Symbol> = extras
+                .iter()
+                .chain(subset.iter().flat_map(|(_, c)| c))
+                .unique()
+                .copied()
+                .collect();
+            if symbols.len() != size + 1 {
+                continue;
+            }
+            let mut did_update = false;
+            for (cell, _) in others.iter().filter(|o| !subset.contains(o)) {
+                for symbol in &symbols {
+                    did_update |= sudoku.remove_candidate(cell, symbol)?;
+                }
+            }
+            if did_update {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
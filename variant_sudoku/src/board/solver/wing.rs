@@ -0,0 +1,159 @@
+use itertools::Itertools;
+use log::debug;
+
+use crate::{
+    board::{
+        constraints::standard::{get_cells_in_house, is_complete_house, House, HouseSet},
+        digit::{Candidates, Symbol},
+        sudoku::{Cell, DidUpdateGrid, Peers},
+    },
+    errors::SudokuError,
+    Sudoku,
+};
+
+/// An XY-Wing has a pivot with candidates XY, which sees two pincers with candidates XZ and YZ.
+/// Whichever the pivot is, one of the pincers has to be Z,
+/// so Z can be removed from every cell seeing both pincers.
+pub(crate) fn xy_wing(
+    sudoku: &mut Sudoku,
+    _houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running XY-Wing, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let peers = sudoku.get_peers();
+    let bivalue = get_cells_with_candidates(sudoku, 2);
+    for (pivot, pivot_candidates) in &bivalue {
+        let pincers = bivalue
+            .iter()
+            .filter(|(c, candidates)| {
+                peers.sees(pivot, c) && shared(candidates, pivot_candidates).len() == 1
+            })
+            .tuple_combinations();
+        for ((a, a_candidates), (b, b_candidates)) in pincers {
+            let z = match shared(a_candidates, b_candidates).as_slice() {
+                [z] if !pivot_candidates.contains(z) => *z,
+                _ => continue,
+            };
+            if remove_from_common_peers(sudoku, &peers, &[*a, *b], &z)? {
+                debug!("Found XY-Wing with pivot {pivot:?} and pincers {a:?} and {b:?} on {z:?}");
+                sudoku.note_regions(&[[*pivot, *a, *b]]);
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// An XYZ-Wing is an XY-Wing whose pivot can also be Z, so Z is only removed from the cells
+/// seeing the pivot as well as both pincers.
+pub(crate) fn xyz_wing(
+    sudoku: &mut Sudoku,
+    _houses: &HouseSet,
+) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running XYZ-Wing, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let peers = sudoku.get_peers();
+    let bivalue = get_cells_with_candidates(sudoku, 2);
+    for (pivot, pivot_candidates) in get_cells_with_candidates(sudoku, 3) {
+        let pincers = bivalue
+            .iter()
+            .filter(|(c, candidates)| {
+                peers.sees(&pivot, c) && candidates.iter().all(|s| pivot_candidates.contains(s))
+            })
+            .tuple_combinations();
+        for ((a, a_candidates), (b, b_candidates)) in pincers {
+            let z = match shared(a_candidates, b_candidates).as_slice() {
+                [z] => *z,
+                _ => continue,
+            };
+            if remove_from_common_peers(sudoku, &peers, &[pivot, *a, *b], &z)? {
+                debug!("Found XYZ-Wing with pivot {pivot:?} and pincers {a:?} and {b:?} on {z:?}");
+                sudoku.note_regions(&[[pivot, *a, *b]]);
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// A W-Wing is two cells with the same two candidates XY, which do not see each other,
+/// along with a house where every cell that could be X sees one of them.
+/// If neither of the cells were Y, they would both be X and leave that house without one,
+/// so Y can be removed from every cell seeing both of them.
+pub(crate) fn w_wing(sudoku: &mut Sudoku, houses: &HouseSet) -> Result<DidUpdateGrid, SudokuError> {
+    debug!(
+        "Running W-Wing, Entropy is {:.2} ({:?})",
+        sudoku.get_entropy(),
+        sudoku.to_string_line()
+    );
+    let peers = sudoku.get_peers();
+    let complete: Vec<&House> = houses
+        .iter()
+        .filter(|h| is_complete_house(sudoku, h))
+        .collect();
+    let bivalue = get_cells_with_candidates(sudoku, 2);
+    let pairs =
+        bivalue
+            .iter()
+            .tuple_combinations()
+            .filter(|((a, a_candidates), (b, b_candidates))| {
+                a_candidates == b_candidates && !peers.sees(a, b)
+            });
+    for ((a, candidates), (b, _)) in pairs {
+        let Some((p, q)) = candidates.iter().copied().collect_tuple() else {
+            continue;
+        };
+        for (x, y) in [(p, q), (q, p)] {
+            for house in &complete {
+                let x_cells = get_cells_in_house(sudoku, house, &x)?;
+                let is_linked = !x_cells.is_empty()
+                    && x_cells
+                        .iter()
+                        .all(|c| c != a && c != b && (peers.sees(c, a) || peers.sees(c, b)));
+                if !is_linked {
+                    continue;
+                }
+                if remove_from_common_peers(sudoku, &peers, &[*a, *b], &y)? {
+                    debug!("Found W-Wing on {a:?} and {b:?} linked by {x:?} in {house:?}");
+                    sudoku.note_regions(&[&[*a, *b], house.as_slice()]);
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Every unsolved cell with exactly `count` candidates.
+fn get_cells_with_candidates(sudoku: &Sudoku, count: usize) -> Vec<(Cell, Candidates)> {
+    sudoku
+        .indexed_candidates()
+        .into_iter()
+        .filter(|(_, candidates)| candidates.len() == count)
+        .map(|(cell, candidates)| (cell, candidates.clone()))
+        .collect()
+}
+
+fn shared(a: &Candidates, b: &Candidates) -> Vec<Symbol> {
+    a.iter().filter(|s| b.contains(s)).copied().collect()
+}
+
+/// Removes `symbol` from every cell which sees all of `cells`.
+pub(crate) fn remove_from_common_peers(
+    sudoku: &mut Sudoku,
+    peers: &Peers,
+    cells: &[Cell],
+    symbol: &Symbol,
+) -> Result<DidUpdateGrid, SudokuError> {
+    let mut did_update = false;
+    for cell in peers.common(cells) {
+        did_update |= sudoku.remove_candidate(&cell, symbol)?;
+    }
+    Ok(did_update)
+}
@@ -0,0 +1,142 @@
+use std::rc::Rc;
+
+use crate::{
+    board::{
+        constraints::{
+            arrow::ArrowSum,
+            killer::CageCombinations,
+            kropki::KropkiDot,
+            thermo::{Thermometer, ThermometerInCage},
+        },
+        solver::{
+            house::HOUSE_STRATEGIES, killer::KILLER_STRATEGIES, uniqueness::UNIQUENESS_STRATEGIES,
+        },
+        sudoku::DidUpdateGrid,
+    },
+    errors::SudokuError,
+    Sudoku,
+};
+
+pub type RcStrategy = Rc<dyn Strategy>;
+
+/// A technique the solver uses to make progress, on top of the propagation each constraint
+/// does for itself.
+///
+/// Strategies are registered with a `SolverConfig`, so other crates can add their own.
+pub trait Strategy {
+    /// The name the strategy is logged under, and enabled or disabled by.
+    fn name(&self) -> &str;
+
+    /// How hard the strategy is to spot, on the scale of Sudoku Explainer ratings.
+    fn weight(&self) -> f32;
+
+    /// Whether the strategy has anything to work with in this sudoku, such as a killer
+    /// strategy needing cages.
+    fn is_applicable(&self, _sudoku: &Sudoku) -> bool {
+        true
+    }
+
+    /// Makes at most one deduction, returning whether the board changed.
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<DidUpdateGrid, SudokuError>;
+}
+
+/// The strategies the solver tries, in order, until one of them makes progress.
+///
+/// The default has every strategy of this library, easiest first.
+#[derive(Clone)]
+pub struct SolverConfig {
+    /// Every registered strategy, along with whether it is enabled.
+    strategies: Vec<(RcStrategy, bool)>,
+}
+
+impl SolverConfig {
+    /// A config without any strategies, leaving only what the constraints propagate.
+    pub fn empty() -> Self {
+        Self { strategies: vec![] }
+    }
+
+    /// Adds a strategy to be tried after the others, or replaces the one with the same name.
+    pub fn add_strategy<S: Strategy + 'static>(&mut self, strategy: S) {
+        let strategy: RcStrategy = Rc::new(strategy);
+        match self.find(strategy.name()) {
+            Ok(i) => self.strategies[i] = (strategy, true),
+            Err(_) => self.strategies.push((strategy, true)),
+        }
+    }
+
+    pub fn enable(&mut self, name: &str) -> Result<(), SudokuError> {
+        let i = self.find(name)?;
+        self.strategies[i].1 = true;
+        Ok(())
+    }
+
+    pub fn disable(&mut self, name: &str) -> Result<(), SudokuError> {
+        let i = self.find(name)?;
+        self.strategies[i].1 = false;
+        Ok(())
+    }
+
+    /// Moves the named strategies to the front, in the order given, with the rest after them.
+    ///
+    /// The config is left as it was if any name is unknown or given more than once.
+    pub fn set_order(&mut self, names: &[&str]) -> Result<(), SudokuError> {
+        let mut indices = Vec::with_capacity(names.len());
+        for name in names {
+            let i = self.find(name)?;
+            if indices.contains(&i) {
+                return Err(SudokuError::DuplicateStrategy(name.to_string()));
+            }
+            indices.push(i);
+        }
+        let mut ordered: Vec<(RcStrategy, bool)> = indices
+            .iter()
+            .map(|i| self.strategies[*i].clone())
+            .collect();
+        ordered.extend(
+            self.strategies
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !indices.contains(i))
+                .map(|(_, s)| s.clone()),
+        );
+        self.strategies = ordered;
+        Ok(())
+    }
+
+    /// The enabled strategies, in the order they are tried.
+    pub fn get_strategies(&self) -> impl Iterator<Item = &RcStrategy> {
+        self.strategies
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(strategy, _)| strategy)
+    }
+
+    fn find(&self, name: &str) -> Result<usize, SudokuError> {
+        self.strategies
+            .iter()
+            .position(|(s, _)| s.name() == name)
+            .ok_or_else(|| SudokuError::UnknownStrategy(name.to_string()))
+    }
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        let mut strategies: Vec<RcStrategy> = HOUSE_STRATEGIES
+            .iter()
+            .chain(UNIQUENESS_STRATEGIES)
+            .map(|s| Rc::new(*s) as RcStrategy)
+            .chain(KILLER_STRATEGIES.iter().map(|s| Rc::new(*s) as RcStrategy))
+            .chain([
+                Rc::new(CageCombinations) as RcStrategy,
+                Rc::new(ArrowSum),
+                Rc::new(KropkiDot),
+                Rc::new(Thermometer),
+                Rc::new(ThermometerInCage),
+            ])
+            .collect();
+        strategies.sort_by(|a, b| a.weight().total_cmp(&b.weight()));
+        Self {
+            strategies: strategies.into_iter().map(|s| (s, true)).collect(),
+        }
+    }
+}
@@ -1,79 +1,269 @@
 use grid::Grid;
+use itertools::Itertools;
 use log::{debug, trace, warn};
 
 use crate::{
     board::{
         constraints::{
-            standard::{get_box_size, HouseSet, HouseUnique},
+            killer::{Killer, SumCombinations},
+            standard::{House, HouseSet, HouseUnique},
             RcConstraint,
         },
-        digit::{Candidates, Digit, Symbol},
+        digit::{Candidates, Digit, Symbol, MAX_SYMBOLS},
+        rating::Rating,
         solution::{Solution, SolutionString},
+        solve_log::{SolveLog, SolveStep},
+        solver::brute_force::{brute_force, BRUTE_FORCE_WEIGHT},
+        strategy::SolverConfig,
     },
     errors::SudokuError,
 };
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::{self, Debug, Display},
     rc::Rc,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Cell {
     pub row: usize,
     pub col: usize,
 }
 
+impl Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "r{}c{}", self.row + 1, self.col + 1)
+    }
+}
+
 pub(crate) type DidUpdateGrid = bool;
 type Board = Grid<Digit>;
 type Constraints = Vec<RcConstraint>;
 
+/// Two solutions are enough to know a puzzle is not unique.
+pub const DEFAULT_SOLUTION_LIMIT: usize = 2;
+
 #[derive(Clone)]
 pub struct Sudoku {
     board: Board,
     pub(crate) valid_symbols: HashSet<Symbol>,
+    /// Every valid symbol as candidates, which the candidates of each cell are built from.
+    pub(crate) all_candidates: Candidates,
+    /// The number each symbol stands for in arithmetic constraints, such as killer cages.
+    symbol_values: HashMap<Symbol, u32>,
     pub(crate) constraints: Constraints,
+    pub(crate) sum_combinations: Rc<SumCombinations>,
+    /// Strategies weighted above this are skipped, which `brute_force` uses to keep guessing cheap.
+    pub(crate) max_strategy_weight: f32,
+    /// Whether the puzzle is known to have a single solution, which uniqueness strategies rely on.
+    pub(crate) assume_unique: bool,
+    /// The strategies to solve with, shared between the copies made while guessing.
+    pub(crate) config: Rc<SolverConfig>,
+    /// The steps taken so far, when solving with a log.
+    pub(crate) log: Option<Box<SolveLog>>,
 }
 
 impl Sudoku {
     pub fn empty() -> Self {
-        let digit = Digit(vec![Symbol('0')]);
+        let all_candidates = Candidates::all(Rc::new([Symbol('0')]));
         Sudoku {
-            board: Grid::init(9, 9, digit),
+            board: Grid::init(9, 9, Digit(all_candidates.clone())),
             valid_symbols: HashSet::new(),
+            all_candidates,
+            symbol_values: HashMap::new(),
             constraints: Vec::new(),
+            sum_combinations: Rc::default(),
+            max_strategy_weight: f32::INFINITY,
+            assume_unique: false,
+            config: Rc::default(),
+            log: None,
         }
     }
 
+    /// Solves the sudoku, checking that the solution is unique.
+    ///
+    /// Equivalent to `solve_all(2)`, which is enough to tell a unique puzzle from an ambiguous one.
     pub fn solve(&mut self) -> Result<Solution, SudokuError> {
+        self.solve_all(DEFAULT_SOLUTION_LIMIT)
+    }
+
+    /// Solves the sudoku, collecting at most `limit` solutions (a limit of 0 is treated as 1).
+    ///
+    /// At least two solutions are always searched for, so a lower limit still tells a unique
+    /// solution from several, only returning one of them.
+    /// If the solution is unique, the sudoku is updated in place to the solved board.
+    /// Otherwise only the logical progress made before guessing is kept.
+    pub fn solve_all(&mut self, limit: usize) -> Result<Solution, SudokuError> {
+        self.solve_logically()?;
+        if self.is_unsolveable() {
+            debug!(
+                "Unsolveable, here is sudoku at end: {:?}",
+                self.to_string_line()
+            );
+            return Ok(Solution::NoSolution);
+        }
+        if self.is_solved() {
+            return Ok(Solution::UniqueSolution(self.clone()));
+        }
+        debug!(
+            "No Updates this round, falling back to brute force from: {:?}",
+            self.to_string_line()
+        );
+        // The guesses are logged as a single step, not every strategy tried along the way.
+        let log = self.log.take();
+        let mut solutions = brute_force(self, limit.max(DEFAULT_SOLUTION_LIMIT))?;
+        self.log = log;
+        let ok = match solutions.len() {
+            0 => Solution::NoSolution,
+            1 => {
+                let solved = solutions.remove(0);
+                self.run_step("Brute Force", BRUTE_FORCE_WEIGHT, |sudoku| {
+                    sudoku.board = solved.board;
+                    Ok(true)
+                })?;
+                Solution::UniqueSolution(self.clone())
+            }
+            _ => {
+                solutions.truncate(limit.max(1));
+                Solution::MultipleSolutions(solutions)
+            }
+        };
+        Ok(ok)
+    }
+
+    /// Solves the sudoku like `solve`, also returning each step taken along the way.
+    ///
+    /// Steps are only logged for progress made after this is called, so the givens are never
+    /// part of the log.
+    pub fn solve_with_log(&mut self) -> Result<(Solution, SolveLog), SudokuError> {
+        self.log = Some(Box::default());
+        let solution = self.solve();
+        let log = self.log.take().unwrap_or_default();
+        Ok((solution?, *log))
+    }
+
+    /// Rates how hard the sudoku is, solving a copy of it with the easiest technique available
+    /// at each step, so harder strategies are only counted when nothing easier makes progress.
+    ///
+    /// Only a sudoku with a unique solution can be rated. Naked singles are not counted,
+    /// see `Rating` for how this compares with Sudoku Explainer.
+    pub fn rate(&self) -> Result<Rating, SudokuError> {
+        let weights = self.get_strategy_weights();
+        let mut sudoku = self.clone();
+        sudoku.log = Some(Box::default());
+        while !sudoku.is_solved()
+            && !sudoku.is_unsolveable()
+            && sudoku.use_easiest_strategy(&weights)?
+        {}
+        // Anything left is finished by guessing, which is rated above every strategy.
+        match sudoku.solve()? {
+            Solution::UniqueSolution(_) => {}
+            _ => {
+                return Err(SudokuError::InvalidBoard(
+                    "Only a sudoku with a unique solution can be rated".to_string(),
+                ))
+            }
+        }
+        Ok(Rating::new(*sudoku.log.take().unwrap_or_default()))
+    }
+
+    /// Finds the easiest deduction which can be made next, without changing the sudoku.
+    ///
+    /// The step says which technique to use, the regions to look at, and what it places or
+    /// removes. Returns `None` once the sudoku is solved, or when only guessing could go on.
+    pub fn next_hint(&self) -> Result<Option<SolveStep>, SudokuError> {
+        if self.is_solved() || self.is_unsolveable() {
+            return Ok(None);
+        }
+        let mut sudoku = self.clone();
+        sudoku.log = Some(Box::default());
+        sudoku.use_easiest_strategy(&self.get_strategy_weights())?;
+        Ok(sudoku.log.and_then(|log| log.steps.into_iter().next()))
+    }
+
+    /// Counts the solutions of the sudoku, stopping once `limit` have been found.
+    ///
+    /// Setters can use `count_solutions(2) == 1` to check a puzzle is unique.
+    pub fn count_solutions(&self, limit: usize) -> Result<usize, SudokuError> {
+        let ok = match self.clone().solve_all(limit)? {
+            Solution::NoSolution => 0,
+            Solution::UniqueSolution(_) | Solution::PreComputed(_) => 1,
+            Solution::MultipleSolutions(solutions) => solutions.len(),
+        };
+        Ok(ok)
+    }
+
+    /// Runs the strategies of every constraint until the sudoku is solved, becomes unsolveable,
+    /// or no strategy is able to make any more progress.
+    pub(crate) fn solve_logically(&mut self) -> Result<(), SudokuError> {
         loop {
             debug!("Sudoku after iteration: {:?}", self.to_string_line());
-            if self.is_solved() {
-                return Ok(Solution::UniqueSolution(self.clone()));
+            if self.is_solved() || self.is_unsolveable() {
+                return Ok(());
             }
-            if self.is_unsolveable() {
+            if !self.use_strategies()? {
                 debug!(
-                    "Unsolveable, here is sudoku at end: {:?}",
+                    "No Updates this round, here is sudoku at end: {:?}",
                     self.to_string_line()
                 );
-                return Ok(Solution::NoSolution);
-            }
-            let mut did_update = false;
-            for constraint in self.constraints.clone() {
-                did_update |= constraint.use_strategies(self)?;
-                if did_update {
-                    break;
-                }
+                return Ok(());
             }
-            if !did_update {
-                debug!(
-                "No Updates this round, here is sudoku at end: {:?}",
-                self.to_string_line()
-            );
-            return Ok(Solution::NoSolution);
+        }
+    }
+
+    /// Runs the strategies of the solver config, stopping at the first to make progress.
+    fn use_strategies(&mut self) -> Result<DidUpdateGrid, SudokuError> {
+        for strategy in self.config.clone().get_strategies() {
+            if strategy.is_applicable(self)
+                && self.run_step(strategy.name(), strategy.weight(), |s| strategy.apply(s))?
+            {
+                return Ok(true);
             }
+        }
+        Ok(false)
+    }
 
+    /// The weight of every strategy the solver config can run, easiest first.
+    fn get_strategy_weights(&self) -> Vec<f32> {
+        self.config
+            .get_strategies()
+            .filter(|s| s.is_applicable(self))
+            .map(|s| s.weight())
+            .filter(|w| *w <= self.max_strategy_weight)
+            .sorted_by(f32::total_cmp)
+            .dedup()
+            .collect()
+    }
+
+    /// Makes progress with the easiest strategy able to, by allowing each of `weights` in turn.
+    fn use_easiest_strategy(&mut self, weights: &[f32]) -> Result<DidUpdateGrid, SudokuError> {
+        let max_strategy_weight = self.max_strategy_weight;
+        let mut did_update = false;
+        for weight in weights {
+            self.max_strategy_weight = *weight;
+            did_update = self.use_strategies()?;
+            if did_update {
+                break;
+            }
         }
+        self.max_strategy_weight = max_strategy_weight;
+        Ok(did_update)
+    }
+
+    /// Lets the solver use strategies which are only sound for a puzzle with a single solution,
+    /// such as Unique Rectangles. On a puzzle with more than one solution they can remove
+    /// candidates belonging to real solutions, so only set this for a puzzle known to be unique.
+    pub fn set_assume_unique(&mut self, assume_unique: bool) {
+        self.assume_unique = assume_unique;
+    }
+
+    /// Sets which strategies the solver uses, and in what order.
+    pub fn set_solver_config(&mut self, config: SolverConfig) {
+        self.config = Rc::new(config);
+    }
+
+    pub fn get_solver_config(&self) -> &SolverConfig {
+        &self.config
     }
 
     pub fn is_solved(&self) -> bool {
@@ -82,6 +272,7 @@ impl Sudoku {
 
     pub fn is_unsolveable(&self) -> bool {
         self.board.iter().any(|d| d.0.is_empty())
+            || self.constraints.iter().any(|c| c.is_violated(self))
     }
 
     pub fn size(&self) -> (usize, usize) {
@@ -134,7 +325,11 @@ impl Sudoku {
         SolutionString(self.board.iter().map(Digit::get_char).collect())
     }
 
-    pub(crate) fn new(givens: Grid<Option<Symbol>>, constraints: Constraints) -> Self {
+    pub(crate) fn new(
+        givens: Grid<Option<Symbol>>,
+        constraints: Constraints,
+        value_overrides: HashMap<Symbol, u32>,
+    ) -> Result<Self, SudokuError> {
         let distinct_symbols = givens.rows().max(givens.cols());
         let mut valid_symbols: HashSet<Symbol> = givens.iter().filter_map(|f| *f).collect();
         let remaining_options = "1234567890ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
@@ -146,22 +341,53 @@ impl Sudoku {
             }
             valid_symbols.insert(i);
         }
-        Self::new_with_valid_digits(givens, constraints, valid_symbols)
+        Self::new_with_valid_digits(givens, constraints, valid_symbols, value_overrides)
     }
 
+    /// Symbols are worth their value as a base 36 digit (so `A` is 10), unless given
+    /// a value in `value_overrides`.
+    ///
+    /// This makes `0` worth 0, even in a 10x10 grid using `1` to `0`, where it usually stands
+    /// for 10. Such a grid needs an override of 10 for `0` if its cages count it that way.
     pub(crate) fn new_with_valid_digits(
         givens: Grid<Option<Symbol>>,
         constraints: Constraints,
         valid_symbols: HashSet<Symbol>,
-    ) -> Self {
+        value_overrides: HashMap<Symbol, u32>,
+    ) -> Result<Self, SudokuError> {
         debug!("Givens {givens:?}");
+        if valid_symbols.len() > MAX_SYMBOLS {
+            return Err(SudokuError::InvalidBoard(format!(
+                "Cannot have {} symbols, at most {MAX_SYMBOLS} are supported",
+                valid_symbols.len()
+            )));
+        }
+        if let Some(symbol) = value_overrides.keys().find(|s| !valid_symbols.contains(s)) {
+            return Err(SudokuError::InvalidBoard(format!(
+                "Cannot give a value to {symbol:?}, which is not a valid symbol"
+            )));
+        }
+        let mut symbol_values: HashMap<Symbol, u32> = valid_symbols
+            .iter()
+            .filter_map(|s| s.0.to_digit(36).map(|v| (*s, v)))
+            .collect();
+        symbol_values.extend(value_overrides);
         let (rows, cols) = givens.size();
-        let all_symbols_digit = Digit(valid_symbols.iter().cloned().collect());
-        let board = Grid::init(rows, cols, all_symbols_digit);
+        let mut sorted_symbols: Vec<Symbol> = valid_symbols.iter().cloned().collect();
+        sorted_symbols.sort_by_key(|s| s.0);
+        let all_candidates = Candidates::all(sorted_symbols.into());
+        let board = Grid::init(rows, cols, Digit(all_candidates.clone()));
         let mut sudoku = Sudoku {
             board,
+            sum_combinations: Rc::new(SumCombinations::new(&symbol_values)),
             valid_symbols,
+            all_candidates,
+            symbol_values,
             constraints,
+            max_strategy_weight: f32::INFINITY,
+            assume_unique: false,
+            config: Rc::default(),
+            log: None,
         };
         debug!(
             "New Sudoku created, with size {:?}, and valid symbols: {:?}",
@@ -172,9 +398,14 @@ impl Sudoku {
             .indexed_iter()
             .filter_map(|(c, f)| f.as_ref().map(|v| (Cell { row: c.0, col: c.1 }, v)))
         {
-            sudoku.place_digit(&cell, symbol).unwrap();
+            sudoku.place_digit(&cell, symbol)?;
         }
-        sudoku
+        Ok(sudoku)
+    }
+
+    /// The number `symbol` stands for, if it has one.
+    pub fn get_value(&self, symbol: &Symbol) -> Option<u32> {
+        self.symbol_values.get(symbol).copied()
     }
 
     /// Makes the Symbol the only one in that cell.
@@ -184,7 +415,7 @@ impl Sudoku {
         symbol: &Symbol,
     ) -> Result<DidUpdateGrid, SudokuError> {
         let before = self.get_cell(cell)?;
-        let digit = Digit(vec![*symbol]);
+        let digit = Digit(self.all_candidates.with_symbols([symbol]));
         if *before == digit {
             return Ok(false);
         }
@@ -209,10 +440,9 @@ impl Sudoku {
         //     warn!("Cannot remove {symbol_to_remove:#} from {cell:?}, it is already solved with {cell_mut:?}");
         //     return Ok(false);
         // }
-        if !cell_mut.0.contains(symbol_to_remove) {
+        if !cell_mut.0.remove(symbol_to_remove) {
             return Ok(false);
         }
-        cell_mut.0.retain(|f| f != symbol_to_remove);
         let candidates_left = &self.get_cell(cell)?.0;
         trace!(
             "Candidates left after removal: {candidates_left:?}, Entropy is now {:.2}",
@@ -228,18 +458,17 @@ impl Sudoku {
     }
 
     // Keeps the candidate as an option from that cell (similar to an intersection)
-    pub fn keep_candidates<I>(&mut self, cells: I, symbols_to_keep: &Candidates) -> Result<DidUpdateGrid, SudokuError>
+    pub fn keep_candidates<I>(&mut self, cells: I, symbols_to_keep: &[Symbol]) -> Result<DidUpdateGrid, SudokuError>
     where
         I: IntoIterator<Item = Cell> + Clone,
     {
         let mut did_update = false;
         let mut needs_notification = vec![];
+        let to_keep = self.all_candidates.with_symbols(symbols_to_keep);
         for cell in cells.clone() {
             let cell_mut = self.get_cell_mut(&cell)?;
-            let before = cell_mut.clone();
-            debug!("Keeping Only {symbols_to_keep:?} from {cell:?}. Before {before:?}");
-            cell_mut.0.retain(|f| symbols_to_keep.contains(f));
-            if before != *cell_mut {
+            debug!("Keeping Only {symbols_to_keep:?} from {cell:?}. Before {cell_mut:?}");
+            if cell_mut.0.keep(&to_keep) {
                 did_update = true;
                 needs_notification.push(cell);
             }
@@ -256,6 +485,72 @@ impl Sudoku {
         Ok(did_update)
     }
 
+    /// Runs a strategy as one step of the log, recording what changed on the board if it
+    /// made any progress. Without a log this just runs the strategy.
+    ///
+    /// Strategies weighing more than `max_strategy_weight` are skipped.
+    pub(crate) fn run_step<F>(
+        &mut self,
+        technique: &str,
+        weight: f32,
+        strategy: F,
+    ) -> Result<DidUpdateGrid, SudokuError>
+    where
+        F: FnOnce(&mut Sudoku) -> Result<DidUpdateGrid, SudokuError>,
+    {
+        if weight > self.max_strategy_weight {
+            return Ok(false);
+        }
+        let before = match &mut self.log {
+            Some(log) => {
+                log.pending_regions.clear();
+                self.board.clone()
+            }
+            None => return strategy(self),
+        };
+        let did_update = strategy(self)?;
+        if !did_update {
+            return Ok(false);
+        }
+        let placements: Vec<(Cell, Symbol)> = self
+            .indexed_iter()
+            .filter(|(cell, _)| !before[(cell.row, cell.col)].is_solved())
+            .filter_map(|(cell, digit)| digit.try_get_solved().map(|s| (cell, *s)))
+            .collect();
+        let cleared = self.get_cleared_by_placements(&placements);
+        let eliminations: Vec<(Cell, Symbol)> = self
+            .indexed_iter()
+            .filter(|(_, digit)| !digit.is_solved())
+            .flat_map(|(cell, digit)| {
+                before[(cell.row, cell.col)]
+                    .0
+                    .iter()
+                    .filter(|s| !digit.0.contains(s))
+                    .map(move |s| (cell, *s))
+            })
+            .filter(|elimination| !cleared.contains(elimination))
+            .collect();
+        if let Some(log) = &mut self.log {
+            log.steps.push(SolveStep {
+                technique: technique.to_owned(),
+                weight,
+                regions: std::mem::take(&mut log.pending_regions),
+                placements,
+                eliminations,
+            });
+        }
+        Ok(true)
+    }
+
+    /// Notes the houses, cages or other cells a strategy drew its deduction from,
+    /// for the step it is making.
+    pub(crate) fn note_regions<R: AsRef<[Cell]>>(&mut self, regions: &[R]) {
+        if let Some(log) = &mut self.log {
+            log.pending_regions
+                .extend(regions.iter().map(|r| r.as_ref().to_vec()));
+        }
+    }
+
     pub(crate) fn notify(&mut self, cell: &Cell) -> Result<DidUpdateGrid, SudokuError> {
         let mut did_update = false;
         for constraint in self.constraints.clone().iter() {
@@ -285,6 +580,112 @@ impl Sudoku {
             .flat_map(|house| house.get_houses(self))
             .collect()
     }
+
+    /// Works out which cells every cell sees, from the houses of every `HouseUnique` constraint
+    /// and every killer cage whose digits must differ.
+    pub(crate) fn get_peers(&self) -> Peers {
+        let (rows, cols) = self.size();
+        let mut peers: Grid<HashSet<Cell>> = Grid::new(rows, cols);
+        let mut sees: Grid<bool> = Grid::new(rows * cols, rows * cols);
+        for region in &self.get_peer_regions() {
+            for (a, b) in region.iter().tuple_combinations() {
+                peers[(a.row, a.col)].insert(*b);
+                peers[(b.row, b.col)].insert(*a);
+                sees[(a.row * cols + a.col, b.row * cols + b.col)] = true;
+                sees[(b.row * cols + b.col, a.row * cols + a.col)] = true;
+            }
+        }
+        Peers { peers, sees, cols }
+    }
+
+    /// The houses and unique cages, within which no symbol can repeat.
+    fn get_peer_regions(&self) -> Vec<House> {
+        let cages = self
+            .constraints
+            .iter()
+            .filter_map(|c| c.as_any().downcast_ref::<Killer>())
+            .flat_map(|k| k.get_cages())
+            .filter(|cage| cage.is_unique())
+            .map(|cage| cage.cells.clone());
+        self.get_houses().into_iter().chain(cages).collect()
+    }
+
+    /// The candidates each of `placements` rules out of the cells seeing it.
+    fn get_cleared_by_placements(&self, placements: &[(Cell, Symbol)]) -> HashSet<(Cell, Symbol)> {
+        let mut cleared = HashSet::new();
+        if placements.is_empty() {
+            return cleared;
+        }
+        for region in self.get_peer_regions() {
+            for (placed, symbol) in placements.iter().filter(|(c, _)| region.contains(c)) {
+                cleared.extend(
+                    region
+                        .iter()
+                        .filter(|c| *c != placed)
+                        .map(|c| (*c, *symbol)),
+                );
+            }
+        }
+        cleared
+    }
+
+    /// Returns the region index of every cell, taken from the first `HouseUnique` constraint
+    /// (other than rows and columns) whose houses cover every cell exactly once.
+    ///
+    /// These are the boxes of a standard sudoku, or the regions of a jigsaw.
+    pub(crate) fn get_regions(&self) -> Option<Grid<usize>> {
+        let (rows, cols) = self.size();
+        self.constraints
+            .iter()
+            .filter_map(|c| c.as_any().downcast_ref::<HouseUnique>())
+            .filter(|house| matches!(house, HouseUnique::Box(_) | HouseUnique::Custom(_)))
+            .find_map(|house| {
+                let mut regions: Grid<Option<usize>> = Grid::new(rows, cols);
+                for (i, h) in house.get_houses(self).iter().enumerate() {
+                    for cell in h {
+                        match regions.get_mut(cell.row, cell.col) {
+                            Some(r @ None) => *r = Some(i),
+                            _ => return None,
+                        }
+                    }
+                }
+                let regions: Option<Vec<usize>> = regions.into_vec().into_iter().collect();
+                regions.map(|r| Grid::from_vec(r, cols))
+            })
+    }
+}
+
+/// The cells each cell sees, which can never hold the same symbol as it.
+pub(crate) struct Peers {
+    peers: Grid<HashSet<Cell>>,
+    /// Whether each pair of cells see each other, by `row * cols + col`, as strategies ask a lot.
+    sees: Grid<bool>,
+    cols: usize,
+}
+
+impl Peers {
+    pub(crate) fn of(&self, cell: &Cell) -> &HashSet<Cell> {
+        &self.peers[(cell.row, cell.col)]
+    }
+
+    pub(crate) fn sees(&self, a: &Cell, b: &Cell) -> bool {
+        self.sees
+            .get(a.row * self.cols + a.col, b.row * self.cols + b.col)
+            .is_some_and(|s| *s)
+    }
+
+    /// The cells seeing every one of `cells`.
+    pub(crate) fn common(&self, cells: &[Cell]) -> Vec<Cell> {
+        let (first, rest) = match cells.split_first() {
+            Some(split) => split,
+            None => return vec![],
+        };
+        self.of(first)
+            .iter()
+            .filter(|c| rest.iter().all(|other| self.sees(c, other)))
+            .copied()
+            .collect()
+    }
 }
 
 impl Display for Sudoku {
@@ -294,24 +695,48 @@ impl Display for Sudoku {
             return Ok(());
         }
         let (board_rows, board_cols) = self.size();
-        let (box_height, box_width) = get_box_size((board_rows, board_cols)).unwrap();
+        let regions = self
+            .get_regions()
+            .unwrap_or_else(|| Grid::new(board_rows, board_cols));
+        let region = |row: usize, col: usize| regions[(row, col)];
+        // A border is drawn on the right of (row, col), or below it.
+        let right_border = |row: usize, col: usize| region(row, col) != region(row, col + 1);
+        let bottom_border = |row: usize, col: usize| region(row, col) != region(row + 1, col);
+        // Only columns and rows with at least one border get a divider, so boxes line up.
+        let divider_cols: Vec<bool> = (0..board_cols.saturating_sub(1))
+            .map(|col| (0..board_rows).any(|row| right_border(row, col)))
+            .collect();
+        let divider_rows: Vec<bool> = (0..board_rows.saturating_sub(1))
+            .map(|row| (0..board_cols).any(|col| bottom_border(row, col)))
+            .collect();
 
         for (i, row) in self.board.iter_rows().enumerate() {
-            if i % box_height == 0 && i != 0 {
+            if i != 0 && divider_rows[i - 1] {
                 // Horizontal divider
                 let mut line = String::new();
                 for col in 0..board_cols {
-                    if col % box_width == 0 && col != 0 {
-                        line.push_str("+-");
+                    if col != 0 && divider_cols[col - 1] {
+                        let vertical = right_border(i - 1, col - 1) || right_border(i, col - 1);
+                        let horizontal =
+                            bottom_border(i - 1, col - 1) || bottom_border(i - 1, col);
+                        line.push(match (vertical, horizontal) {
+                            (true, _) => '+',
+                            (false, true) => '-',
+                            (false, false) => ' ',
+                        });
+                        line.push(if bottom_border(i - 1, col) { '-' } else { ' ' });
                     }
-                    line.push_str("--");
+                    line.push_str(if bottom_border(i - 1, col) { "--" } else { "  " });
                 }
                 writeln!(f, "{line}")?;
             }
 
             for (j, digit) in row.enumerate() {
-                if j % box_width == 0 && j != 0 {
-                    write!(f, "| ")?;
+                if j != 0 && divider_cols[j - 1] {
+                    match right_border(i, j - 1) {
+                        true => write!(f, "| ")?,
+                        false => write!(f, "  ")?,
+                    }
                 }
                 write!(f, "{digit} ")?;
             }
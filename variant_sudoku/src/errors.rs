@@ -10,4 +10,13 @@ pub enum SudokuError {
 
     #[error("Constraint Predicate Invalid: {0}")]
     ConstraintPredicateInvalid(String),
+
+    #[error("Invalid Board: {0}")]
+    InvalidBoard(String),
+
+    #[error("Strategy {0} is not in the solver config")]
+    UnknownStrategy(String),
+
+    #[error("Strategy {0} is given more than once")]
+    DuplicateStrategy(String),
 }
@@ -1,6 +1,9 @@
 pub mod board;
 pub(crate) use board::constraints::Constraint;
+pub use board::rating::Rating;
 pub use board::solution::Solution;
+pub use board::solve_log::{SolveLog, SolveStep};
+pub use board::strategy::{SolverConfig, Strategy};
 pub use board::sudoku::Sudoku;
-use errors::SudokuError;
+pub use errors::SudokuError;
 mod errors;
@@ -1,7 +1,10 @@
 use std::{fs::File, io::Read, path::PathBuf};
 
 use serde::Deserialize;
-use variant_sudoku::{Solution, Sudoku};
+use variant_sudoku::{
+    board::{digit::Symbol, sudoku::Cell},
+    Rating, Solution, SolverConfig, Strategy, Sudoku, SudokuError,
+};
 
 fn test_game(game: &mut Sudoku, expected_solution: Solution) {
     let actual = game.solve().expect("Sudoku should not error");
@@ -56,6 +59,19 @@ fn test_file(path: PathBuf) {
     }
 }
 
+/// Like `test_file`, also checking the sudoku is solved without guessing.
+fn test_file_logically(path: PathBuf) {
+    test_file(path.clone());
+    let mut string_buf = String::new();
+    File::open(path)
+        .unwrap()
+        .read_to_string(&mut string_buf)
+        .expect("Failed to read file");
+    let sudoku: Sudoku = serde_yaml::from_str(&string_buf).expect("Failed to parse YAML");
+    let rating = sudoku.rate().expect("Sudoku should be rated");
+    assert!(rating.is_logical(), "Guessed with a rating of {rating}");
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,17 +99,385 @@ mod tests {
                 test_file(path);
             }
         };
+        ($name:ident, LOGICAL) => {
+            #[test]
+            fn $name() {
+                let name_str = stringify!($name);
+                // Strip "test_" prefix at runtime
+                let stem = name_str.strip_prefix("test_").unwrap();
+                let filename = format!("games/{}.yaml", stem);
+                let path = std::path::PathBuf::from(filename);
+                test_file_logically(path);
+            }
+        };
     }
 
     sudoku_test!(test_easy_standard);
     sudoku_test!(test_trivial_standard);
     sudoku_test!(test_locked_candidate_standard);
+    sudoku_test!(test_naked_subset_standard, LOGICAL);
     sudoku_test!(test_hidden_subset_standard);
+    sudoku_test!(test_x_wing_standard, LOGICAL);
+    sudoku_test!(test_wing_standard, LOGICAL);
+    sudoku_test!(test_claiming_standard);
+    sudoku_test!(test_chain_standard, LOGICAL);
+    sudoku_test!(test_als_standard, LOGICAL);
+    sudoku_test!(test_als_xy_wing_standard, LOGICAL);
+    sudoku_test!(test_unique_rectangle_standard, LOGICAL);
     sudoku_test!(test_medium_standard);
-    sudoku_test!(test_hard_standard, SKIP);
+    sudoku_test!(test_hard_standard, LOGICAL);
     sudoku_test!(test_4x4_standard);
     sudoku_test!(test_6x6_standard);
+    sudoku_test!(test_6x6_tall_box_standard);
+    sudoku_test!(test_6x9_standard);
+    sudoku_test!(test_8x8_standard);
+    sudoku_test!(test_10x10_standard);
+    sudoku_test!(test_12x12_standard);
+    sudoku_test!(test_16x16_standard);
     sudoku_test!(test_easy_killer);
+    sudoku_test!(test_medium_killer, LOGICAL);
+    sudoku_test!(test_zero_based_killer, LOGICAL);
+    sudoku_test!(test_symbol_value_killer, LOGICAL);
+    sudoku_test!(test_required_digit_killer);
+    sudoku_test!(test_two_box_innies_killer);
     sudoku_test!(test_unsolveable_standard);
-    sudoku_test!(test_unsolveable_variants, SKIP);
+    sudoku_test!(test_unsolveable_variants);
+    sudoku_test!(test_multiple_standard);
+    sudoku_test!(test_diagonal_standard);
+    sudoku_test!(test_easy_kropki, LOGICAL);
+    sudoku_test!(test_5x5_jigsaw);
+    sudoku_test!(test_medium_jigsaw);
+    sudoku_test!(test_easy_thermo, LOGICAL);
+    sudoku_test!(test_slow_thermo_killer, LOGICAL);
+    sudoku_test!(test_easy_arrow, LOGICAL);
+    sudoku_test!(test_medium_arrow, LOGICAL);
+    sudoku_test!(test_negative_kropki, LOGICAL);
+    sudoku_test!(test_calculation_killer);
+    sudoku_test!(test_non_unique_killer);
+
+    fn load_game(stem: &str) -> Sudoku {
+        let mut string_buf = String::new();
+        File::open(format!("games/{stem}.yaml"))
+            .unwrap()
+            .read_to_string(&mut string_buf)
+            .expect("Failed to read file");
+        serde_yaml::from_str(&string_buf).expect("Failed to parse YAML")
+    }
+
+    #[test]
+    fn test_multiple_standard_reports_all_solutions() {
+        let mut sudoku = load_game("multiple_standard");
+        match sudoku.solve().expect("Sudoku should not error") {
+            Solution::MultipleSolutions(solutions) => assert_eq!(solutions.len(), 2),
+            other => panic!("Expected multiple solutions, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_solve_all_low_limit_is_not_unique() {
+        for limit in [0, 1] {
+            match load_game("multiple_standard").solve_all(limit).unwrap() {
+                Solution::MultipleSolutions(solutions) => assert_eq!(solutions.len(), 1),
+                other => panic!("Expected multiple solutions, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        assert_eq!(load_game("multiple_standard").count_solutions(10).unwrap(), 2);
+        assert_eq!(load_game("multiple_standard").count_solutions(1).unwrap(), 1);
+        assert_eq!(load_game("hard_standard").count_solutions(2).unwrap(), 1);
+        assert_eq!(load_game("unsolveable_standard").count_solutions(2).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_unique_rectangle_needs_assume_unique() {
+        let mut sudoku = load_game("unique_rectangle_standard");
+        sudoku.set_assume_unique(false);
+        assert!(!sudoku.rate().expect("Sudoku should be rated").is_logical());
+    }
+
+    #[test]
+    fn test_solve_log_replays() {
+        let mut sudoku = load_game("wing_standard");
+        let (solution, log) = sudoku.solve_with_log().expect("Sudoku should not error");
+        let Solution::UniqueSolution(solved) = solution else {
+            panic!("Expected a unique solution, got {solution:?}");
+        };
+        assert!(log.steps.iter().all(|s| s.technique != "Brute Force"));
+        assert!(log.steps.iter().any(|s| s.technique == "Hidden Single"));
+        assert!(log.steps.iter().any(|s| s.weight > 4.0 && !s.regions.is_empty()));
+        assert_eq!(log.to_string().lines().count(), log.steps.len());
+
+        let mut replay = load_game("wing_standard");
+        for step in &log.steps {
+            assert!(!step.placements.is_empty() || !step.eliminations.is_empty());
+            for (cell, symbol) in &step.placements {
+                replay.place_digit(cell, symbol).unwrap();
+            }
+            for (cell, symbol) in &step.eliminations {
+                replay.remove_candidate(cell, symbol).unwrap();
+            }
+        }
+        assert!(replay.is_solved(), "Stalled at {}", replay.to_string_line());
+        assert_eq!(replay.to_string_line(), solved.to_string_line());
+    }
+
+    #[test]
+    fn test_rate_x_wing_standard() {
+        let sudoku = load_game("x_wing_standard");
+        let rating = sudoku.rate().expect("Sudoku should be rated");
+        assert_eq!(rating.max, 3.2);
+        assert!(rating.is_logical());
+        assert!(rating.log.steps.iter().any(|s| s.technique == "X-Wing"));
+        assert!(!sudoku.is_solved());
+    }
+
+    /// Rates a game with every strategy but `keep` disabled.
+    fn rate_with_only(stem: &str, keep: &[&str]) -> Rating {
+        let mut sudoku = load_game(stem);
+        let mut config = SolverConfig::default();
+        let names: Vec<String> = config.get_strategies().map(|s| s.name().to_string()).collect();
+        for name in names.iter().filter(|n| !keep.contains(&n.as_str())) {
+            config.disable(name).unwrap();
+        }
+        sudoku.set_solver_config(config);
+        sudoku.rate().expect("Sudoku should be rated")
+    }
+
+    #[test]
+    fn test_required_digit_killer_solves_from_cages_alone() {
+        // Each cage is narrowed to its combinations, placing digits every combination needs.
+        let rating = rate_with_only("required_digit_killer", &["Cage Combinations"]);
+        assert!(rating.is_logical(), "Guessed with a rating of {rating}");
+    }
+
+    #[test]
+    fn test_two_box_innies() {
+        // Stuck without the innies or outies of two boxes in a stack.
+        let keep = ["Cage Combinations", "Hidden Single", "Innies and Outies"];
+        let rating = rate_with_only("two_box_innies_killer", &keep);
+        assert!(rating.is_logical(), "Guessed with a rating of {rating}");
+    }
+
+    #[test]
+    fn test_rate_killer_counts_cages() {
+        let rating = load_game("easy_killer").rate().expect("Sudoku should be rated");
+        assert!(rating.is_logical());
+        assert!(rating.log.steps.iter().any(|s| s.technique == "Cage Combinations"));
+        let total: f32 = rating.log.steps.iter().map(|s| s.weight).sum();
+        assert!((rating.total - total).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_rate_guessing() {
+        let mut sudoku = load_game("unique_rectangle_standard");
+        sudoku.set_assume_unique(false);
+        let rating = sudoku.rate().expect("Sudoku should be rated");
+        assert!(!rating.is_logical());
+        assert_eq!(rating.log.steps.last().unwrap().technique, "Brute Force");
+    }
+
+    #[test]
+    fn test_claiming_is_a_locked_candidate() {
+        // Only a line into a box has an elimination here.
+        let rating = load_game("claiming_standard").rate().expect("Sudoku should be rated");
+        assert_eq!(rating.log.steps[0].technique, "Locked Candidate");
+    }
+
+    #[test]
+    fn test_solve_log_is_reproducible() {
+        let first = load_game("chain_standard").rate().expect("Sudoku should be rated");
+        let second = load_game("chain_standard").rate().expect("Sudoku should be rated");
+        assert_eq!(first.log, second.log);
+    }
+
+    #[test]
+    fn test_naked_subset_is_needed() {
+        let rating = load_game("naked_subset_standard").rate().expect("Sudoku should be rated");
+        assert!(rating.log.steps.iter().any(|s| s.technique == "Naked Subset"));
+        assert_eq!(rating.max, 2.8);
+    }
+
+    #[test]
+    fn test_rate_multiple_standard() {
+        assert!(load_game("multiple_standard").rate().is_err());
+    }
+
+    #[test]
+    fn test_next_hint_is_easiest_step() {
+        let sudoku = load_game("x_wing_standard");
+        let before = sudoku.to_string_line();
+        let hint = sudoku.next_hint().unwrap().expect("Sudoku should have a hint");
+        assert_eq!(sudoku.to_string_line(), before);
+        let rating = sudoku.rate().unwrap();
+        assert_eq!(hint.weight, rating.log.steps[0].weight);
+        assert!(!hint.get_focus_cells().is_empty());
+
+        let mut applied = sudoku.clone();
+        for (cell, symbol) in &hint.placements {
+            applied.place_digit(cell, symbol).unwrap();
+        }
+        for (cell, symbol) in &hint.eliminations {
+            applied.remove_candidate(cell, symbol).unwrap();
+        }
+        assert_ne!(applied.next_hint().unwrap(), Some(hint));
+    }
+
+    #[test]
+    fn test_next_hint_when_solved() {
+        let mut sudoku = load_game("x_wing_standard");
+        sudoku.solve().unwrap();
+        assert_eq!(sudoku.next_hint().unwrap(), None);
+    }
+
+    #[test]
+    fn test_disabled_strategy_is_not_used() {
+        let mut sudoku = load_game("x_wing_standard");
+        let mut config = SolverConfig::default();
+        config.disable("X-Wing").unwrap();
+        sudoku.set_solver_config(config);
+        let (solution, log) = sudoku.solve_with_log().expect("Sudoku should not error");
+        assert!(matches!(solution, Solution::UniqueSolution(_)));
+        assert!(log.steps.iter().all(|s| s.technique != "X-Wing"));
+    }
+
+    #[test]
+    fn test_disabled_constraint_strategy_is_not_used() {
+        let (_, log) = load_game("easy_killer").solve_with_log().unwrap();
+        assert!(log.steps.iter().any(|s| s.technique == "Cage Combinations"));
+        let mut sudoku = load_game("easy_killer");
+        let mut config = SolverConfig::default();
+        config.disable("Cage Combinations").unwrap();
+        sudoku.set_solver_config(config);
+        let (solution, log) = sudoku.solve_with_log().expect("Sudoku should not error");
+        assert!(matches!(solution, Solution::UniqueSolution(_)));
+        assert!(log.steps.iter().all(|s| s.technique != "Cage Combinations"));
+    }
+
+    #[test]
+    fn test_solver_config_order() {
+        let mut config = SolverConfig::default();
+        config.set_order(&["X-Wing", "Hidden Single"]).unwrap();
+        let names: Vec<&str> = config.get_strategies().map(|s| s.name()).take(3).collect();
+        assert_eq!(names, ["X-Wing", "Hidden Single", "Cage Combinations"]);
+        assert!(config.disable("Not A Strategy").is_err());
+    }
+
+    #[test]
+    fn test_solver_config_order_error_keeps_config() {
+        let mut config = SolverConfig::default();
+        let names = |c: &SolverConfig| -> Vec<String> {
+            c.get_strategies().map(|s| s.name().to_string()).collect()
+        };
+        let before = names(&config);
+        assert!(matches!(
+            config.set_order(&["X-Wing", "Not A Strategy"]),
+            Err(SudokuError::UnknownStrategy(_))
+        ));
+        assert_eq!(names(&config), before);
+        assert!(matches!(
+            config.set_order(&["X-Wing", "Hidden Single", "X-Wing"]),
+            Err(SudokuError::DuplicateStrategy(_))
+        ));
+        assert_eq!(names(&config), before);
+    }
+
+    /// Places the digit of a known solution in the first unsolved cell.
+    struct PeekSolution(Vec<char>);
+
+    impl Strategy for PeekSolution {
+        fn name(&self) -> &str {
+            "Peek Solution"
+        }
+
+        fn weight(&self) -> f32 {
+            1.0
+        }
+
+        fn apply(&self, sudoku: &mut Sudoku) -> Result<bool, SudokuError> {
+            let (rows, cols) = sudoku.size();
+            for row in 0..rows {
+                for col in 0..cols {
+                    let cell = Cell { row, col };
+                    if sudoku.get_cell(&cell)?.0.len() > 1 {
+                        return sudoku.place_digit(&cell, &Symbol(self.0[row * cols + col]));
+                    }
+                }
+            }
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn test_custom_strategy() {
+        let Solution::UniqueSolution(solved) = load_game("x_wing_standard").solve().unwrap() else {
+            panic!("Expected a unique solution");
+        };
+        let solution = solved.to_string_line().to_string();
+        let mut config = SolverConfig::empty();
+        config.add_strategy(PeekSolution(solution.chars().collect()));
+        let mut sudoku = load_game("x_wing_standard");
+        sudoku.set_solver_config(config);
+        let rating = sudoku.rate().expect("Sudoku should be rated");
+        assert_eq!(rating.max, 1.0);
+        assert!(rating.log.steps.iter().all(|s| s.technique == "Peek Solution"));
+        let (_, log) = sudoku.solve_with_log().expect("Sudoku should not error");
+        assert!(log.steps.iter().all(|s| s.technique == "Peek Solution"));
+        assert_eq!(sudoku.to_string_line().to_string(), solution);
+    }
+
+    #[test]
+    fn test_invalid_box_size() {
+        let yaml = "board: |\n  ......\n  ......\n  ......\n  ......\n  ......\n  ......\nconstraints:\n  - name: standard\n    box_size: [4, 4]\n";
+        assert!(serde_yaml::from_str::<Sudoku>(yaml).is_err());
+    }
+
+    #[test]
+    fn test_kropki_dot_outside_grid() {
+        let yaml = "board: |\n  ....\n  ....\n  ....\n  ....\nconstraints:\n  - name: black_kropki\n    cells:\n      - [[3, 3], [3, 4]]\n";
+        let err = serde_yaml::from_str::<Sudoku>(yaml).unwrap_err();
+        assert!(err.to_string().contains("outside the grid"), "{err}");
+    }
+
+    #[test]
+    fn test_value_for_unknown_symbol() {
+        let yaml = "board: |\n  ....\n  ....\n  ....\n  ....\nvalid_digits: \"ABCD\"\nsymbol_values: {Z: 1}\n";
+        let err = serde_yaml::from_str::<Sudoku>(yaml).unwrap_err();
+        assert!(err.to_string().contains("not a valid symbol"), "{err}");
+    }
+
+    #[test]
+    fn test_too_many_symbols() {
+        let digits: String = (0..65u32).filter_map(|i| char::from_u32(0x100 + i)).collect();
+        let yaml = format!("board: |\n  ....\n  ....\n  ....\n  ....\nvalid_digits: \"{digits}\"\n");
+        let err = serde_yaml::from_str::<Sudoku>(&yaml).unwrap_err();
+        assert!(err.to_string().contains("at most 64"), "{err}");
+    }
+
+    #[test]
+    fn test_set_candidates() {
+        let yaml = "board: |\n  ....\n  ....\n  ....\n  ....\nvalid_digits: \"DCBA\"\n";
+        let mut sudoku = serde_yaml::from_str::<Sudoku>(yaml).unwrap();
+        let digit = sudoku.get_cell_mut(&Cell { row: 0, col: 0 }).unwrap();
+        assert_eq!(digit.0.iter().map(|s| s.0).collect::<String>(), "ABCD");
+        digit.set_candidates(&[Symbol('C'), Symbol('Z'), Symbol('A')]);
+        assert_eq!(digit.0.iter().map(|s| s.0).collect::<String>(), "AC");
+        assert!(!digit.0.contains(&Symbol('Z')));
+    }
+
+    #[test]
+    fn test_ragged_board() {
+        let yaml = "board: |\n  1234\n  341\n  4123\n  2341\n";
+        let err = serde_yaml::from_str::<Sudoku>(yaml).unwrap_err();
+        assert!(err.to_string().contains("Line 2"), "{err}");
+    }
+
+    #[test]
+    fn test_disconnected_jigsaw_region() {
+        let yaml = "board: |\n  ....\n  ....\n  ....\n  ....\nconstraints:\n  - name: standard\n  - name: jigsaw\n    regions: |\n      ABAA\n      BABB\n      CCDD\n      CCDD\n";
+        let err = serde_yaml::from_str::<Sudoku>(yaml).unwrap_err();
+        assert!(err.to_string().contains("not orthogonally connected"), "{err}");
+    }
 }
@@ -31,12 +31,14 @@ fn main() -> eframe::Result {
 
 struct SudokuApp {
     sudoku: Sudoku,
+    hint: Option<String>,
 }
 
 impl Default for SudokuApp {
     fn default() -> Self {
         Self {
             sudoku: Sudoku::empty(),
+            hint: None,
         }
     }
 }
@@ -65,8 +67,8 @@ impl eframe::App for SudokuApp {
                                     .min_size(egui::vec2(cell_size, cell_size)),
                             );
                             if cell_ui.changed() {
-                                let symbols = buf.chars().map(|c| Symbol(c)).collect::<Vec<_>>();
-                                *cell = Digit(symbols);
+                                let symbols = buf.chars().map(Symbol).collect::<Vec<_>>();
+                                cell.set_candidates(&symbols);
                             }
                         }
                         ui.end_row();
@@ -80,11 +82,24 @@ impl eframe::App for SudokuApp {
                             eprintln!("Failed to parse the Sudoku file.");
                             Sudoku::empty()
                         });
+                        self.hint = None;
                     }
                 }
 
                 if ui.button("Solve").clicked() {
                     self.sudoku.solve().unwrap();
+                    self.hint = None;
+                }
+
+                if ui.button("Hint").clicked() {
+                    self.hint = Some(match self.sudoku.next_hint() {
+                        Ok(Some(step)) => step.to_string(),
+                        Ok(None) => "No hint available".to_string(),
+                        Err(e) => e.to_string(),
+                    });
+                }
+                if let Some(hint) = &self.hint {
+                    ui.label(hint);
                 }
         });
     }
@@ -6,6 +6,9 @@ use variant_sudoku::Sudoku;
 struct Args {
     path: PathBuf,
     logfile: Option<PathBuf>,
+    /// Rate the difficulty of the sudoku, listing the steps taken.
+    #[arg(long)]
+    rate: bool,
 }
 
 fn main() {
@@ -14,6 +17,12 @@ fn main() {
     let path = File::open(args.path).unwrap();
     let mut sudoku: Sudoku = serde_yaml::from_reader(path).unwrap();
     println!("Loaded:\n{sudoku}");
+    if args.rate {
+        match sudoku.rate() {
+            Ok(rating) => println!("{}Rating: {rating}", rating.log),
+            Err(e) => println!("Could not rate Sudoku: {e}"),
+        }
+    }
     let start_time = time::Instant::now();
     let solved = sudoku.solve().expect("Failed to solve Sudoku");
     let end_time = time::Instant::now();